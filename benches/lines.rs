@@ -0,0 +1,58 @@
+//! Throughput of the crisp Bresenham line (`draw_line`) against the
+//! anti-aliased Wu line (`draw_line_aa`).
+//!
+//! Both draw the same fixed set of pseudo-random segments; criterion reports
+//! the result as lines per second (`cargo bench`).
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use m3str3_cube::rendering::{draw_line, draw_line_aa};
+
+const WIDTH: usize = 1000;
+const HEIGHT: usize = 800;
+const SEGMENTS: usize = 1000;
+
+/// Generates `SEGMENTS` segments inside the surface from a fixed seed, so
+/// every run measures the same work.
+fn segments() -> Vec<((f32, f32), (f32, f32))> {
+    let mut state: u32 = 0x9E37_79B9;
+    let mut next = |max: usize| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        (state % max as u32) as f32
+    };
+    (0..SEGMENTS)
+        .map(|_| ((next(WIDTH), next(HEIGHT)), (next(WIDTH), next(HEIGHT))))
+        .collect()
+}
+
+fn bench_lines(c: &mut Criterion) {
+    let segments = segments();
+    let mut buffer = vec![0u32; WIDTH * HEIGHT];
+
+    let mut group = c.benchmark_group("lines");
+    group.throughput(Throughput::Elements(SEGMENTS as u64));
+
+    group.bench_function("bresenham", |b| {
+        b.iter(|| {
+            for &((x0, y0), (x1, y1)) in &segments {
+                let start = (x0 as usize, y0 as usize);
+                let end = (x1 as usize, y1 as usize);
+                draw_line(black_box(&mut buffer), WIDTH, HEIGHT, start, end, 0xFFFFFF);
+            }
+        })
+    });
+
+    group.bench_function("wu", |b| {
+        b.iter(|| {
+            for &(start, end) in &segments {
+                draw_line_aa(black_box(&mut buffer), WIDTH, HEIGHT, start, end, 0xFFFFFF, false);
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_lines);
+criterion_main!(benches);
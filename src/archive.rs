@@ -0,0 +1,85 @@
+//! Loading models packed in `.zip` archives (`zip` feature).
+
+use crate::model::{self, extension_of, parse_model, Model};
+use std::{cell::RefCell, fs::File, io::{Read, Seek}};
+
+/// Loads the first model file found in a `.zip` archive.
+/// ---------------------------------------------------------------------
+/// Entries are searched in archive order and the first one with a model
+/// extension (`.obj`, `.ply`, `.dxf`, `.stl`) is parsed like a file on disk.
+/// Files it refers to, such as the `.mtl` libraries of an OBJ, are read
+/// from the archive, relative to the model's directory inside it.
+pub fn load_zip(file_path: &str, curve_samples: usize) -> Result<Model, String> {
+    let file = File::open(file_path)
+        .map_err(|e| format!("Could not open file: {}", e))?;
+    read_zip(file, curve_samples)
+}
+
+/// Loads the first model of a `.zip` archive read from `reader`, like `load_zip`.
+fn read_zip(reader: impl Read + Seek, curve_samples: usize) -> Result<Model, String> {
+    let mut archive = zip::ZipArchive::new(reader)
+        .map_err(|e| format!("Could not read ZIP archive: {}", e))?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)
+            .map_err(|e| format!("Could not read ZIP entry: {}", e))?;
+        let name = entry.name().to_string();
+        let is_model = !entry.is_dir()
+            && extension_of(&name).is_some_and(|ext| ext != "zip" && model::MODEL_EXTENSIONS.contains(&ext.as_str()));
+        if !is_model {
+            continue;
+        }
+
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)
+            .map_err(|e| format!("Could not read {} from the archive: {}", name, e))?;
+        drop(entry);
+        println!("Loading {} from the archive", name);
+
+        // Referenced files are entry names relative to the model's directory
+        let dir = name.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let archive = RefCell::new(archive);
+        let read_file = |file: &str| {
+            let file = file.trim_start_matches("./");
+            let path = if dir.is_empty() { file.to_string() } else { format!("{}/{}", dir, file) };
+            let mut archive = archive.borrow_mut();
+            let mut entry = archive.by_name(&path).ok()?;
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).ok()?;
+            Some(data)
+        };
+        return parse_model(&name, &contents, curve_samples, &read_file);
+    }
+
+    Err("No .obj, .ply, .dxf or .stl model found in the archive".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+
+    /// A `.zip` archive holding the given files.
+    fn archive_with(files: &[(&str, &str)]) -> Cursor<Vec<u8>> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        let mut archive = writer.finish().unwrap();
+        archive.set_position(0);
+        archive
+    }
+
+    #[test]
+    fn obj_materials_are_read_from_the_archive() {
+        let obj = "mtllib plate.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl red\nf 1 2 3\n";
+        let mtl = "newmtl red\nKd 1 0 0\n";
+        let archive = archive_with(&[("readme.txt", "A red plate"), ("models/plate.obj", obj), ("models/plate.mtl", mtl)]);
+
+        let model = read_zip(archive, 16).unwrap();
+        assert_eq!(model.faces.len(), 1);
+        assert_eq!(model.face_colors, vec![Some(0xFF0000)]);
+    }
+}
@@ -0,0 +1,58 @@
+//! Orbit camera: the point the view circles, how far away it is, which way
+//! it looks and how wide it sees.
+
+use m3str3_cube::transformations::{self, Mat3, Vec3};
+
+/// Default vertical field of view, in degrees. At the default window height
+/// it gives a scale of about 600 pixels per unit at distance 1.
+pub const DEFAULT_FOV: f32 = 67.4;
+
+/// A camera orbiting `target` at `distance`. The model is turned by `pitch`
+/// around X, then by `yaw` around Y, in front of the camera.
+#[derive(Clone, Copy, Debug)]
+pub struct Camera {
+    pub target: Vec3,
+    pub distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    /// Vertical field of view, in radians.
+    pub fov: f32,
+}
+
+impl Camera {
+    /// A camera looking at `target` from `distance` away along the Z axis.
+    pub fn new(target: Vec3, distance: f32, fov: f32) -> Camera {
+        Camera { target, distance, yaw: 0.0, pitch: 0.0, fov }
+    }
+
+    /// Rotation from model space (relative to the target) into camera space.
+    pub fn rotation(&self) -> Mat3 {
+        transformations::build_rotation_matrix(self.pitch, self.yaw, 0.0)
+    }
+
+    /// The camera's right, up and forward directions in model space: the
+    /// columns of the inverse (transposed) rotation.
+    pub fn axes(&self) -> (Vec3, Vec3, Vec3) {
+        let orientation = self.rotation().transpose();
+        (
+            orientation * Vec3::new(1.0, 0.0, 0.0),
+            orientation * Vec3::new(0.0, 1.0, 0.0),
+            orientation * Vec3::new(0.0, 0.0, 1.0),
+        )
+    }
+
+    /// Projection scale, in pixels per unit at distance 1, of a viewport
+    /// `height` pixels tall:
+    /// $$ \text{scale} = \frac{\text{height} / 2}{\tan(\text{fov} / 2)} $$
+    pub fn scale(&self, height: usize) -> f32 {
+        height as f32 / 2.0 / (self.fov / 2.0).tan()
+    }
+
+    /// Moves the target across the view by a mouse drag of `(dx, dy)` pixels,
+    /// so that points at the target's depth follow the mouse.
+    pub fn pan(&mut self, (dx, dy): (f32, f32), scale: f32) {
+        let (right, up, _) = self.axes();
+        let units = self.distance / scale;
+        self.target = self.target - right * (dx * units) + up * (dy * units);
+    }
+}
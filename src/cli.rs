@@ -0,0 +1,312 @@
+//! Command-line options for the model viewer.
+
+use minifb::{Scale, ScaleMode};
+use m3str3_cube::rendering::SamplePattern;
+use m3str3_cube::transformations::DEFAULT_NEAR;
+use crate::camera::DEFAULT_FOV;
+
+pub const DEFAULT_WIDTH: usize = 1000;
+pub const DEFAULT_HEIGHT: usize = 800;
+pub const DEFAULT_CURVE_SAMPLES: usize = 16;
+pub const DEFAULT_THUMBNAIL_SIZE: usize = 256;
+pub const DEFAULT_DEPTH_ALPHA: (f32, f32) = (1.0, 0.3);
+pub const DEFAULT_BACKGROUND_FPS: usize = 4;
+pub const DEFAULT_ROTATE_SPEED: f32 = 36.0;
+
+/// Options parsed from the command line.
+pub struct Options {
+    pub model_path: Option<String>,
+    pub width: usize,
+    pub height: usize,
+    /// Window scale factor; the frame is rendered at `width / scale` and
+    /// upscaled by minifb.
+    pub scale: Scale,
+    /// How the frame fills a resized window. Without it the frame buffer is
+    /// resized along with the window instead.
+    pub scale_mode: Option<ScaleMode>,
+    /// How often the window is polled while it is unfocused; rendering pauses
+    /// until it gets the focus back.
+    pub background_fps: usize,
+    /// Auto-rotation speed in degrees per second, independent of the frame rate.
+    pub rotate_speed: f32,
+    /// Vertical field of view in degrees; sets the projection scale.
+    pub fov: f32,
+    /// Sample pattern of the MSAA wireframe (`--msaa-samples 1|2|4`).
+    pub msaa_pattern: SamplePattern,
+    /// Near-plane distance: points closer to the camera are clipped.
+    pub near: f32,
+    /// Wireframe opacity of the nearest and farthest edges for depth-based alpha.
+    pub depth_alpha: (f32, f32),
+    /// Colors cycled by B and M (`--palette RRGGBB,RRGGBB,...`), replacing the defaults.
+    pub palette: Option<Vec<u32>>,
+    /// Background color as `0xRRGGBB` (`--bg RRGGBB`), added to the palette B cycles.
+    pub background: Option<u32>,
+    /// Object color as `0xRRGGBB` (`--color RRGGBB`), added to the palette M cycles.
+    pub color: Option<u32>,
+    /// Image drawn behind the model as a modeling reference.
+    pub reference_path: Option<String>,
+    /// Unit label shown with the model's measurements (e.g. `mm`).
+    pub units: Option<String>,
+    /// Scale loaded models so their largest dimension is 1.
+    pub normalize: bool,
+    /// Flip faces whose normals point towards the model's centroid on load.
+    pub orient_outward: bool,
+    /// Save screenshots with a transparent background (alpha 0 where nothing was drawn).
+    pub transparent: bool,
+    /// Print the model's element counts and memory footprint and exit.
+    pub info: bool,
+    /// Load every model under this directory, report which fail and exit.
+    pub validate_dir: Option<String>,
+    /// Render a PNG thumbnail of every model under this directory and exit.
+    pub thumbnails_dir: Option<String>,
+    /// Directory the thumbnails are written to (`--out`).
+    pub out_dir: Option<String>,
+    /// Width and height of each thumbnail in pixels.
+    pub thumbnail_size: usize,
+    /// Camera angles `(x, y)` of the thumbnails, in radians.
+    pub thumbnail_angles: (f32, f32),
+    /// Write the model as DXF to this path and exit without opening a window.
+    pub dxf_path: Option<String>,
+    /// Wireframe line thickness in pixels.
+    pub line_width: usize,
+    /// Segments per OBJ free-form curve (per span for Bezier curves).
+    pub curve_samples: usize,
+    /// Camera path file played back as a flythrough.
+    pub camera_path: Option<String>,
+    /// Directory that flythrough frames are saved to as PPM images.
+    pub frames_dir: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            model_path: None,
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            scale: Scale::X1,
+            scale_mode: None,
+            background_fps: DEFAULT_BACKGROUND_FPS,
+            rotate_speed: DEFAULT_ROTATE_SPEED,
+            fov: DEFAULT_FOV,
+            msaa_pattern: SamplePattern::X4,
+            near: DEFAULT_NEAR,
+            depth_alpha: DEFAULT_DEPTH_ALPHA,
+            palette: None,
+            background: None,
+            color: None,
+            reference_path: None,
+            units: None,
+            normalize: false,
+            orient_outward: false,
+            transparent: false,
+            info: false,
+            validate_dir: None,
+            thumbnails_dir: None,
+            out_dir: None,
+            thumbnail_size: DEFAULT_THUMBNAIL_SIZE,
+            thumbnail_angles: (30f32.to_radians(), 45f32.to_radians()),
+            dxf_path: None,
+            line_width: 1,
+            curve_samples: DEFAULT_CURVE_SAMPLES,
+            camera_path: None,
+            frames_dir: None,
+        }
+    }
+}
+
+/// Parses the program arguments (without the program name) into `Options`.
+/// ---------------------------------------------------------------------
+/// Flags take their value from the next argument (`--width 1280`), except
+/// switches such as `--info`.
+/// The first argument that is not a flag is taken as the model path.
+pub fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--width" => options.width = parse_positive(arg, iter.next())?,
+            "--height" => options.height = parse_positive(arg, iter.next())?,
+            "--scale" => options.scale = parse_scale(arg, iter.next())?,
+            "--scale-mode" => options.scale_mode = Some(parse_scale_mode(arg, iter.next())?),
+            "--line-width" => options.line_width = parse_positive(arg, iter.next())?,
+            "--curve-samples" => options.curve_samples = parse_positive(arg, iter.next())?,
+            "--camera-path" => options.camera_path = Some(expect_value(arg, iter.next())?.clone()),
+            "--frames" => options.frames_dir = Some(expect_value(arg, iter.next())?.clone()),
+            "--background-fps" => options.background_fps = parse_positive(arg, iter.next())?,
+            "--rotate-speed" => options.rotate_speed = parse_speed(arg, iter.next())?,
+            "--fov" => options.fov = parse_fov(arg, iter.next())?,
+            "--msaa-samples" => options.msaa_pattern = parse_sample_pattern(arg, iter.next())?,
+            "--near" => options.near = parse_near(arg, iter.next())?,
+            "--depth-alpha" => options.depth_alpha = parse_alpha_pair(arg, iter.next())?,
+            "--palette" => match parse_palette(arg, iter.next()) {
+                Ok(palette) => options.palette = Some(palette),
+                Err(err) => eprintln!("{}; using the default palette", err),
+            },
+            "--bg" => options.background = Some(parse_hex_color(arg, iter.next())?),
+            "--color" => options.color = Some(parse_hex_color(arg, iter.next())?),
+            "--reference" => options.reference_path = Some(expect_value(arg, iter.next())?.clone()),
+            "--units" => options.units = Some(expect_value(arg, iter.next())?.clone()),
+            "--info" => options.info = true,
+            "--transparent" => options.transparent = true,
+            "--normalize" => options.normalize = true,
+            "--orient-outward" => options.orient_outward = true,
+            "--validate" => options.validate_dir = Some(expect_value(arg, iter.next())?.clone()),
+            "--thumbnails" => options.thumbnails_dir = Some(expect_value(arg, iter.next())?.clone()),
+            "--out" => options.out_dir = Some(expect_value(arg, iter.next())?.clone()),
+            "--thumb-size" => options.thumbnail_size = parse_positive(arg, iter.next())?,
+            "--thumb-angle" => options.thumbnail_angles = parse_angles(arg, iter.next())?,
+            "--dxf" => options.dxf_path = Some(expect_value(arg, iter.next())?.clone()),
+            flag if flag.starts_with("--") => {
+                return Err(format!("Unknown flag: {}", flag));
+            }
+            path => {
+                if options.model_path.is_some() {
+                    return Err(format!("Unexpected argument: {}", path));
+                }
+                options.model_path = Some(path.to_string());
+            }
+        }
+    }
+
+    if options.thumbnails_dir.is_some() && options.out_dir.is_none() {
+        return Err("--thumbnails needs an output directory (--out DIR)".to_string());
+    }
+
+    Ok(options)
+}
+
+/// Returns the value following `flag`, or an error if it's missing.
+fn expect_value<'a>(flag: &str, value: Option<&'a String>) -> Result<&'a String, String> {
+    value.ok_or_else(|| format!("Missing value for {}", flag))
+}
+
+/// Parses a positive count such as a window dimension, rejecting missing,
+/// non-numeric and zero values.
+fn parse_positive(flag: &str, value: Option<&String>) -> Result<usize, String> {
+    let value = expect_value(flag, value)?;
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("Invalid value for {}: {}", flag, value)),
+    }
+}
+
+/// Parses an `X,Y` pair of angles in degrees, returned in radians.
+fn parse_angles(flag: &str, value: Option<&String>) -> Result<(f32, f32), String> {
+    let value = expect_value(flag, value)?;
+    let angles: Vec<f32> = value.split(',').filter_map(|a| a.trim().parse().ok()).collect();
+    match angles[..] {
+        [x, y] => Ok((x.to_radians(), y.to_radians())),
+        _ => Err(format!("Invalid value for {}: {} (expected X,Y in degrees)", flag, value)),
+    }
+}
+
+/// Parses a rotation speed in degrees per second; negative speeds turn the
+/// other way.
+fn parse_speed(flag: &str, value: Option<&String>) -> Result<f32, String> {
+    let value = expect_value(flag, value)?;
+    match value.parse::<f32>() {
+        Ok(speed) if speed.is_finite() => Ok(speed),
+        _ => Err(format!("Invalid value for {}: {}", flag, value)),
+    }
+}
+
+/// Parses a field of view in degrees, strictly between 0 and 180.
+fn parse_fov(flag: &str, value: Option<&String>) -> Result<f32, String> {
+    let value = expect_value(flag, value)?;
+    match value.parse::<f32>() {
+        Ok(fov) if fov > 0.0 && fov < 180.0 => Ok(fov),
+        _ => Err(format!("Invalid value for {}: {}", flag, value)),
+    }
+}
+
+/// Parses an MSAA sample count: 1, 2 or 4.
+fn parse_sample_pattern(flag: &str, value: Option<&String>) -> Result<SamplePattern, String> {
+    let value = expect_value(flag, value)?;
+    match value.as_str() {
+        "1" => Ok(SamplePattern::X1),
+        "2" => Ok(SamplePattern::X2),
+        "4" => Ok(SamplePattern::X4),
+        _ => Err(format!("Invalid value for {}: {} (expected 1, 2 or 4)", flag, value)),
+    }
+}
+
+/// Parses a `RRGGBB` hex color (a leading `#` is allowed) into `0xRRGGBB`.
+fn parse_hex_color(flag: &str, value: Option<&String>) -> Result<u32, String> {
+    let value = expect_value(flag, value)?;
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    match u32::from_str_radix(hex, 16) {
+        Ok(color) if hex.len() == 6 && hex.bytes().all(|b| b.is_ascii_hexdigit()) => Ok(color),
+        _ => Err(format!("Invalid value for {}: {} (expected a RRGGBB hex color)", flag, value)),
+    }
+}
+
+/// Parses a comma-separated list of `RRGGBB` hex colors.
+fn parse_palette(flag: &str, value: Option<&String>) -> Result<Vec<u32>, String> {
+    let value = expect_value(flag, value)?;
+    value
+        .split(',')
+        .map(|hex| parse_hex_color(flag, Some(&hex.trim().to_string())))
+        .collect()
+}
+
+/// Parses a positive near-plane distance.
+fn parse_near(flag: &str, value: Option<&String>) -> Result<f32, String> {
+    let value = expect_value(flag, value)?;
+    match value.parse::<f32>() {
+        Ok(near) if near > 0.0 => Ok(near),
+        _ => Err(format!("Invalid value for {}: {}", flag, value)),
+    }
+}
+
+/// Parses a `NEAR,FAR` pair of opacities in `0..=1`.
+fn parse_alpha_pair(flag: &str, value: Option<&String>) -> Result<(f32, f32), String> {
+    let value = expect_value(flag, value)?;
+    let alphas: Vec<f32> = value.split(',').filter_map(|a| a.trim().parse().ok()).collect();
+    match alphas[..] {
+        [near, far] if (0.0..=1.0).contains(&near) && (0.0..=1.0).contains(&far) => Ok((near, far)),
+        _ => Err(format!("Invalid value for {}: {} (expected NEAR,FAR between 0 and 1)", flag, value)),
+    }
+}
+
+/// Parses a window scale: `1`, `2`, `4`, `8` or `fit` (largest that fits the screen).
+fn parse_scale(flag: &str, value: Option<&String>) -> Result<Scale, String> {
+    let value = expect_value(flag, value)?;
+    match value.as_str() {
+        "1" => Ok(Scale::X1),
+        "2" => Ok(Scale::X2),
+        "4" => Ok(Scale::X4),
+        "8" => Ok(Scale::X8),
+        "fit" => Ok(Scale::FitScreen),
+        _ => Err(format!("Invalid value for {}: {} (expected 1, 2, 4, 8 or fit)", flag, value)),
+    }
+}
+
+/// Parses a scale mode: `stretch`, `fit` (keep the aspect ratio), `center`
+/// or `upper-left`.
+fn parse_scale_mode(flag: &str, value: Option<&String>) -> Result<ScaleMode, String> {
+    let value = expect_value(flag, value)?;
+    match value.as_str() {
+        "stretch" => Ok(ScaleMode::Stretch),
+        "fit" => Ok(ScaleMode::AspectRatioStretch),
+        "center" => Ok(ScaleMode::Center),
+        "upper-left" => Ok(ScaleMode::UpperLeft),
+        _ => Err(format!(
+            "Invalid value for {}: {} (expected stretch, fit, center or upper-left)",
+            flag, value
+        )),
+    }
+}
+
+/// Integer factor the window is upscaled by, used to shrink the frame buffer
+/// so the window keeps its requested size. `FitScreen` keeps the full size.
+pub fn scale_factor(scale: Scale) -> usize {
+    match scale {
+        Scale::X2 => 2,
+        Scale::X4 => 4,
+        Scale::X8 => 8,
+        Scale::X16 => 16,
+        Scale::X32 => 32,
+        Scale::X1 | Scale::FitScreen => 1,
+    }
+}
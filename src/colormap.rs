@@ -0,0 +1,124 @@
+//! Colormaps for visualizing scalar values (depth, degree, intensity...).
+//!
+//! Every map takes a value normalized to `[0, 1]` (values outside are clamped)
+//! and returns a packed `0xRRGGBB` color, the same format used by the buffer.
+
+/// Available colormap schemes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Scheme {
+    Grayscale,
+    Viridis,
+    Jet,
+    Turbo,
+}
+
+impl Scheme {
+    /// Returns the scheme after this one, wrapping around (used to cycle maps).
+    pub fn next(self) -> Scheme {
+        match self {
+            Scheme::Grayscale => Scheme::Viridis,
+            Scheme::Viridis => Scheme::Jet,
+            Scheme::Jet => Scheme::Turbo,
+            Scheme::Turbo => Scheme::Grayscale,
+        }
+    }
+}
+
+/// Viridis sampled at 9 evenly spaced points, interpolated linearly in between.
+const VIRIDIS: [(f32, f32, f32); 9] = [
+    (68.0, 1.0, 84.0),
+    (71.0, 44.0, 122.0),
+    (59.0, 81.0, 139.0),
+    (44.0, 113.0, 142.0),
+    (33.0, 144.0, 141.0),
+    (39.0, 173.0, 129.0),
+    (92.0, 200.0, 99.0),
+    (170.0, 220.0, 50.0),
+    (253.0, 231.0, 37.0),
+];
+
+/// Maps `value01` to a color using the given `scheme`.
+pub fn map(value01: f32, scheme: Scheme) -> u32 {
+    let t = if value01.is_nan() { 0.0 } else { value01.clamp(0.0, 1.0) };
+    match scheme {
+        Scheme::Grayscale => grayscale(t),
+        Scheme::Viridis => viridis(t),
+        Scheme::Jet => jet(t),
+        Scheme::Turbo => turbo(t),
+    }
+}
+
+/// Black (0) to white (1).
+pub fn grayscale(t: f32) -> u32 {
+    pack(t, t, t)
+}
+
+/// Perceptually uniform dark purple (0) to yellow (1).
+pub fn viridis(t: f32) -> u32 {
+    let scaled = t * (VIRIDIS.len() - 1) as f32;
+    let i = (scaled as usize).min(VIRIDIS.len() - 2);
+    let f = scaled - i as f32;
+    let (r0, g0, b0) = VIRIDIS[i];
+    let (r1, g1, b1) = VIRIDIS[i + 1];
+    pack(
+        (r0 + (r1 - r0) * f) / 255.0,
+        (g0 + (g1 - g0) * f) / 255.0,
+        (b0 + (b1 - b0) * f) / 255.0,
+    )
+}
+
+/// Classic rainbow: dark blue (0), cyan, yellow, dark red (1).
+///
+/// Each channel is a clamped triangle: \( c = 1.5 - |4t - k| \) with
+/// \( k = 3, 2, 1 \) for red, green and blue.
+pub fn jet(t: f32) -> u32 {
+    let channel = |k: f32| 1.5 - (4.0 * t - k).abs();
+    pack(channel(3.0), channel(2.0), channel(1.0))
+}
+
+/// Turbo, an improved rainbow; uses the polynomial approximation published
+/// alongside the original map.
+pub fn turbo(t: f32) -> u32 {
+    let r = 0.135_721_38 + t * (4.615_392_6 + t * (-42.660_324 + t * (132.131_08 + t * (-152.942_4 + t * 59.286_38))));
+    let g = 0.091_402_61 + t * (2.194_188_4 + t * (4.842_966_6 + t * (-14.185_033 + t * (4.277_298_6 + t * 2.829_566))));
+    let b = 0.106_673_3 + t * (12.641_946 + t * (-60.582_05 + t * (110.362_77 + t * (-89.903_11 + t * 27.348_25))));
+    pack(r, g, b)
+}
+
+/// Packs RGB channels in `[0, 1]` (clamped) into a `0xRRGGBB` color.
+fn pack(r: f32, g: f32, b: f32) -> u32 {
+    let to_byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u32;
+    (to_byte(r) << 16) | (to_byte(g) << 8) | to_byte(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMES: [Scheme; 4] = [Scheme::Grayscale, Scheme::Viridis, Scheme::Jet, Scheme::Turbo];
+
+    #[test]
+    fn endpoints_and_midpoints() {
+        // (scheme, t = 0, t = 0.5, t = 1)
+        let expected = [
+            (Scheme::Grayscale, 0x000000, 0x808080, 0xFFFFFF),
+            (Scheme::Viridis, 0x440154, 0x21908D, 0xFDE725),
+            (Scheme::Jet, 0x000080, 0x80FF80, 0x800000),
+            (Scheme::Turbo, 0x23171B, 0x96FA50, 0x900D00),
+        ];
+        for (scheme, low, mid, high) in expected {
+            assert_eq!(map(0.0, scheme), low, "{:?} at 0", scheme);
+            assert_eq!(map(0.5, scheme), mid, "{:?} at 0.5", scheme);
+            assert_eq!(map(1.0, scheme), high, "{:?} at 1", scheme);
+        }
+    }
+
+    #[test]
+    fn out_of_range_values_are_clamped() {
+        for scheme in SCHEMES {
+            assert_eq!(map(-0.5, scheme), map(0.0, scheme), "{:?}", scheme);
+            assert_eq!(map(1.5, scheme), map(1.0, scheme), "{:?}", scheme);
+            assert_eq!(map(f32::NAN, scheme), map(0.0, scheme), "{:?}", scheme);
+        }
+    }
+}
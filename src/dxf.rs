@@ -0,0 +1,126 @@
+//! Loader for line-only ASCII DXF drawings.
+
+use crate::model::{Model, EDGE_LINE};
+use m3str3_cube::transformations::Point3;
+use std::collections::HashMap;
+
+/// Builds a model from points, merging points with identical coordinates
+/// so consecutive lines share their vertices.
+struct Builder {
+    model: Model,
+    index: HashMap<(u32, u32, u32), usize>,
+}
+
+impl Builder {
+    fn vertex(&mut self, (x, y, z): Point3) -> usize {
+        let vertices = &mut self.model.vertices;
+        *self.index.entry((x.to_bits(), y.to_bits(), z.to_bits())).or_insert_with(|| {
+            vertices.push((x, y, z));
+            vertices.len() - 1
+        })
+    }
+
+    /// Adds a polyline through `points`, closing it back to the start if `closed`.
+    fn polyline(&mut self, points: &[Point3], closed: bool) {
+        let indices: Vec<usize> = points.iter().map(|&p| self.vertex(p)).collect();
+        for pair in indices.windows(2) {
+            self.model.add_edge(pair[0], pair[1], EDGE_LINE);
+        }
+        if closed && indices.len() > 2 {
+            self.model.add_edge(indices[indices.len() - 1], indices[0], EDGE_LINE);
+        }
+    }
+}
+
+/// Parses the `LINE`, `POLYLINE` and `LWPOLYLINE` entities of an ASCII `.dxf` file.
+/// ---------------------------------------------------------------------
+/// A DXF file is a flat list of (group code, value) pairs, one per line.
+/// Code `0` starts a new entity; codes 10/20/30 hold a point's X/Y/Z and,
+/// for `LINE`, 11/21/31 its end point. A `POLYLINE` is followed by `VERTEX`
+/// entities up to a `SEQEND`, while an `LWPOLYLINE` repeats 10/20 pairs at
+/// the elevation given by code 38. Bit 1 of code 70 closes a polyline.
+/// Only the `ENTITIES` section is read; other entity types are skipped.
+pub fn parse_dxf(contents: &str) -> Result<Model, String> {
+    let lines: Vec<&str> = contents.lines().map(str::trim).collect();
+
+    let mut pairs: Vec<(i32, &str)> = Vec::new();
+    for pair in lines.chunks_exact(2) {
+        let code = pair[0]
+            .parse()
+            .map_err(|_| format!("Invalid group code: {}", pair[0]))?;
+        pairs.push((code, pair[1]));
+    }
+
+    // Split the ENTITIES section into entities: a name and its group codes
+    let mut entities: Vec<(&str, Vec<(i32, &str)>)> = Vec::new();
+    let mut in_entities = false;
+    for &(code, value) in &pairs {
+        match (code, value) {
+            (2, "ENTITIES") => in_entities = true,
+            (0, "ENDSEC") => in_entities = false,
+            (0, name) if in_entities => entities.push((name, Vec::new())),
+            _ if in_entities => {
+                if let Some((_, groups)) = entities.last_mut() {
+                    groups.push((code, value));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut builder = Builder { model: Model::default(), index: HashMap::new() };
+    let mut polyline: Option<(Vec<Point3>, bool)> = None;
+
+    for (name, groups) in &entities {
+        let number = |code: i32| {
+            groups
+                .iter()
+                .find(|&&(c, _)| c == code)
+                .and_then(|&(_, v)| v.parse::<f32>().ok())
+                .unwrap_or(0.0)
+        };
+        let closed = (number(70) as u32) & 1 != 0;
+
+        match *name {
+            "LINE" => {
+                let start = (number(10), number(20), number(30));
+                let end = (number(11), number(21), number(31));
+                builder.polyline(&[start, end], false);
+            }
+            "POLYLINE" => polyline = Some((Vec::new(), closed)),
+            "VERTEX" => {
+                if let Some((points, _)) = &mut polyline {
+                    points.push((number(10), number(20), number(30)));
+                }
+            }
+            "SEQEND" => {
+                if let Some((points, closed)) = polyline.take() {
+                    builder.polyline(&points, closed);
+                }
+            }
+            "LWPOLYLINE" => {
+                let elevation = number(38);
+                let mut points: Vec<Point3> = Vec::new();
+                for &(code, value) in groups {
+                    let v = value.parse::<f32>().unwrap_or(0.0);
+                    match code {
+                        10 => points.push((v, 0.0, elevation)),
+                        20 => {
+                            if let Some(point) = points.last_mut() {
+                                point.1 = v;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                builder.polyline(&points, closed);
+            }
+            _ => {}
+        }
+    }
+
+    if builder.model.edges.is_empty() {
+        return Err("No LINE or POLYLINE entities found".to_string());
+    }
+    Ok(builder.model)
+}
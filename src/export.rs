@@ -0,0 +1,155 @@
+//! Writers for exporting the current view or model to other formats.
+
+use crate::model::{self, Model};
+use m3str3_cube::rendering::BACKGROUND_TAG;
+use std::{fs::File, io::Write, path::Path};
+
+/// A projected edge: screen-space start and end points plus its color.
+pub type Segment = ((usize, usize), (usize, usize), u32);
+
+/// Writes projected edges as an SVG document of `width` x `height` pixels.
+/// ---------------------------------------------------------------------
+/// The background becomes a full-size `<rect>` and every segment a `<line>`
+/// in its own color, so the file matches what was drawn on screen but stays
+/// crisp at any zoom level.
+pub fn write_svg(
+    file_path: &str,
+    width: usize,
+    height: usize,
+    background: u32,
+    segments: &[Segment],
+) -> Result<(), String> {
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = width,
+        h = height
+    ));
+    svg.push_str(&format!(
+        "  <rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+        hex_color(background)
+    ));
+    svg.push_str("  <g stroke-width=\"1\" stroke-linecap=\"round\">\n");
+    for &((x1, y1), (x2, y2), color) in segments {
+        svg.push_str(&format!(
+            "    <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\"/>\n",
+            x1, y1, x2, y2, hex_color(color)
+        ));
+    }
+    svg.push_str("  </g>\n</svg>\n");
+
+    let mut file = File::create(file_path).map_err(|e| format!("Could not create file: {}", e))?;
+    file.write_all(svg.as_bytes())
+        .map_err(|e| format!("Could not write file: {}", e))
+}
+
+/// Writes the model's edges as 3D `LINE` entities in an ASCII DXF file.
+/// ---------------------------------------------------------------------
+/// Uses the original (un-rotated, un-projected) vertex coordinates and the
+/// minimal R12 layout that CAD tools accept: a `HEADER` section with the
+/// version, an `ENTITIES` section and `EOF`. Every line goes on layer `0`.
+/// Triangulation diagonals are left out, as they aren't part of the source geometry.
+/// DXF is a list of (group code, value) pairs, one per line; codes 10/20/30
+/// hold the start point and 11/21/31 the end point of a `LINE`.
+pub fn write_dxf(file_path: &str, model: &Model) -> Result<(), String> {
+    let mut dxf = String::new();
+    dxf.push_str("0\nSECTION\n2\nHEADER\n9\n$ACADVER\n1\nAC1009\n0\nENDSEC\n");
+    dxf.push_str("0\nSECTION\n2\nENTITIES\n");
+    for (&(i1, i2), &flags) in model.edges.iter().zip(&model.edge_flags) {
+        if model::is_diagonal_only(flags) {
+            continue;
+        }
+        let (x1, y1, z1) = model.vertices[i1];
+        let (x2, y2, z2) = model.vertices[i2];
+        dxf.push_str(&format!(
+            "0\nLINE\n8\n0\n10\n{}\n20\n{}\n30\n{}\n11\n{}\n21\n{}\n31\n{}\n",
+            x1, y1, z1, x2, y2, z2
+        ));
+    }
+    dxf.push_str("0\nENDSEC\n0\nEOF\n");
+
+    let mut file = File::create(file_path).map_err(|e| format!("Could not create file: {}", e))?;
+    file.write_all(dxf.as_bytes())
+        .map_err(|e| format!("Could not write file: {}", e))
+}
+
+/// Writes the model as a Wavefront OBJ file that `load_obj` reads back.
+/// ---------------------------------------------------------------------
+/// Every vertex becomes a `v` line (with its `r g b` color, if it has one)
+/// and every face an `f` line (OBJ indices are 1-based), with an `o` line
+/// starting each part of a multi-object model. Edges that aren't on a face's boundary, such as those of
+/// `l` elements or DXF lines, become two-point `l` lines so wireframe-only
+/// models survive the round trip; triangulation diagonals are left out,
+/// as `add_face` recreates them.
+pub fn save_obj(model: &Model, path: &str) -> Result<(), String> {
+    let mut obj = String::new();
+    for (i, &(x, y, z)) in model.vertices.iter().enumerate() {
+        match model.vertex_colors.get(i).copied().flatten() {
+            Some(color) => {
+                let channel = |shift: u32| ((color >> shift) & 0xFF) as f32 / 255.0;
+                obj.push_str(&format!("v {} {} {} {} {} {}\n", x, y, z, channel(16), channel(8), channel(0)));
+            }
+            None => obj.push_str(&format!("v {} {} {}\n", x, y, z)),
+        }
+    }
+    for (f, face) in model.faces.iter().enumerate() {
+        if let Some(object) = model.objects.iter().find(|o| o.faces.start == f && !o.faces.is_empty()) {
+            obj.push_str(&format!("o {}\n", object.name));
+        }
+        let indices: Vec<String> = face.iter().map(|i| (i + 1).to_string()).collect();
+        obj.push_str(&format!("f {}\n", indices.join(" ")));
+    }
+    for (&(i1, i2), &flags) in model.edges.iter().zip(&model.edge_flags) {
+        if flags & model::EDGE_FACE == 0 && !model::is_diagonal_only(flags) {
+            obj.push_str(&format!("l {} {}\n", i1 + 1, i2 + 1));
+        }
+    }
+
+    let mut file = File::create(path).map_err(|e| format!("Could not create file: {}", e))?;
+    file.write_all(obj.as_bytes())
+        .map_err(|e| format!("Could not write file: {}", e))
+}
+
+/// Writes a frame buffer as a binary PPM (`P6`) image.
+/// ---------------------------------------------------------------------
+/// PPM is a tiny uncompressed format (a text header, then 3 bytes per pixel)
+/// that most image tools and `ffmpeg` read, which makes it handy for dumping
+/// frame sequences.
+pub fn write_ppm(file_path: &Path, width: usize, height: usize, buffer: &[u32]) -> Result<(), String> {
+    let mut ppm = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    for &pixel in &buffer[..width * height] {
+        ppm.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8]);
+    }
+
+    let mut file = File::create(file_path).map_err(|e| format!("Could not create file: {}", e))?;
+    file.write_all(&ppm)
+        .map_err(|e| format!("Could not write file: {}", e))
+}
+
+/// Writes a `width` x `height` frame of packed `0xRRGGBB` pixels as a PNG image.
+pub fn write_png(file_path: &str, width: usize, height: usize, buffer: &[u32]) -> Result<(), String> {
+    let mut rgb = Vec::with_capacity(width * height * 3);
+    for &pixel in &buffer[..width * height] {
+        rgb.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8]);
+    }
+    image::save_buffer(file_path, &rgb, width as u32, height as u32, image::ExtendedColorType::Rgb8)
+        .map_err(|e| format!("Could not write file: {}", e))
+}
+
+/// Like `write_png`, with an alpha channel: pixels still holding the cleared
+/// background (tagged with `BACKGROUND_TAG`) are fully transparent and every
+/// drawn pixel is opaque, for compositing the model over other images.
+pub fn write_png_transparent(file_path: &str, width: usize, height: usize, buffer: &[u32]) -> Result<(), String> {
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for &pixel in &buffer[..width * height] {
+        let alpha = if pixel & 0xFF00_0000 == BACKGROUND_TAG { 0 } else { 255 };
+        rgba.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8, alpha]);
+    }
+    image::save_buffer(file_path, &rgba, width as u32, height as u32, image::ExtendedColorType::Rgba8)
+        .map_err(|e| format!("Could not write file: {}", e))
+}
+
+/// Formats a packed `0xRRGGBB` color as `#rrggbb` (any alpha byte is ignored).
+fn hex_color(color: u32) -> String {
+    format!("#{:06x}", color & 0xFFFFFF)
+}
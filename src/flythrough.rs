@@ -0,0 +1,93 @@
+//! Camera flythroughs: a list of viewpoints played back with eased interpolation.
+
+use std::fs;
+
+/// Seconds spent travelling to a keyframe that doesn't give its own time.
+const DEFAULT_SECONDS: f32 = 2.0;
+
+/// One viewpoint of a camera path.
+#[derive(Clone, Copy)]
+pub struct Keyframe {
+    pub angle_x: f32,
+    pub angle_y: f32,
+    pub distance: f32,
+    /// Time taken to reach this keyframe from the previous one.
+    pub seconds: f32,
+}
+
+/// A sequence of keyframes, sampled by time.
+pub struct CameraPath {
+    keyframes: Vec<Keyframe>,
+}
+
+impl CameraPath {
+    /// Loads a camera path from a text file.
+    /// ---------------------------------------------------------------------
+    /// Each non-empty line is a keyframe `angle_x angle_y distance [seconds]`,
+    /// with angles in degrees and `seconds` the time to reach it from the
+    /// previous keyframe (default 2). Text after `#` is a comment.
+    pub fn load(file_path: &str) -> Result<CameraPath, String> {
+        let contents = fs::read_to_string(file_path)
+            .map_err(|e| format!("Could not open file: {}", e))?;
+
+        let mut keyframes = Vec::new();
+        for line in contents.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let values: Result<Vec<f32>, _> = line.split_whitespace().map(str::parse::<f32>).collect();
+            match values.as_deref() {
+                Ok([ax, ay, d]) => keyframes.push(keyframe(*ax, *ay, *d, DEFAULT_SECONDS)),
+                Ok([ax, ay, d, s]) if *s > 0.0 => keyframes.push(keyframe(*ax, *ay, *d, *s)),
+                _ => return Err(format!("Invalid keyframe in line: {}", line)),
+            }
+        }
+
+        if keyframes.is_empty() {
+            return Err("Camera path has no keyframes".to_string());
+        }
+        Ok(CameraPath { keyframes })
+    }
+
+    /// Total playback time in seconds (the first keyframe is the start).
+    pub fn duration(&self) -> f32 {
+        self.keyframes.iter().skip(1).map(|k| k.seconds).sum()
+    }
+
+    /// View `(angle_x, angle_y, distance)` at `time` seconds into the path.
+    /// Each leg eases in and out (smoothstep), so the camera comes to rest on
+    /// every keyframe instead of changing direction abruptly.
+    pub fn sample(&self, time: f32) -> (f32, f32, f32) {
+        let mut start = self.keyframes[0];
+        let mut remaining = time.max(0.0);
+
+        for &end in &self.keyframes[1..] {
+            if remaining < end.seconds {
+                let t = remaining / end.seconds;
+                let t = t * t * (3.0 - 2.0 * t);
+                let lerp = |a: f32, b: f32| a + (b - a) * t;
+                return (
+                    lerp(start.angle_x, end.angle_x),
+                    lerp(start.angle_y, end.angle_y),
+                    lerp(start.distance, end.distance),
+                );
+            }
+            remaining -= end.seconds;
+            start = end;
+        }
+
+        (start.angle_x, start.angle_y, start.distance)
+    }
+}
+
+/// Builds a keyframe from angles given in degrees.
+fn keyframe(angle_x: f32, angle_y: f32, distance: f32, seconds: f32) -> Keyframe {
+    Keyframe {
+        angle_x: angle_x.to_radians(),
+        angle_y: angle_y.to_radians(),
+        distance,
+        seconds,
+    }
+}
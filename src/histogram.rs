@@ -0,0 +1,29 @@
+//! Bucketed counts of a set of values, such as the model's edge lengths.
+
+/// Counts of values falling into equal-width buckets between `min` and `max`.
+pub struct Histogram {
+    pub counts: Vec<usize>,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Histogram {
+    /// Sorts `values` into `buckets` equal-width buckets spanning their range.
+    /// The maximum goes into the last bucket; if all values are equal they
+    /// all land in the first one.
+    pub fn new(values: &[f32], buckets: usize) -> Histogram {
+        let (min, max) = values
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        let (min, max) = if values.is_empty() { (0.0, 0.0) } else { (min, max) };
+        let span = (max - min).max(f32::EPSILON);
+
+        let mut counts = vec![0; buckets];
+        for &v in values {
+            let bucket = (((v - min) / span) * buckets as f32) as usize;
+            counts[bucket.min(buckets - 1)] += 1;
+        }
+
+        Histogram { counts, min, max }
+    }
+}
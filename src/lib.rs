@@ -0,0 +1,6 @@
+//! Library half of the viewer, exposing the drawing primitives and the
+//! geometry helpers so they can be benchmarked (see `benches/lines.rs`) and
+//! used outside the viewer.
+
+pub mod rendering;
+pub mod transformations;
@@ -0,0 +1,86 @@
+//! Lighting model used to shade filled faces.
+
+use m3str3_cube::transformations::Vec3;
+
+/// Maximum number of directional lights in a scene.
+pub const MAX_LIGHTS: usize = 4;
+
+/// Lights added with the "add light" key, in order. The first one is the
+/// default key light shining from the camera.
+const PRESETS: [DirectionalLight; MAX_LIGHTS] = [
+    DirectionalLight { direction: (0.0, 0.0, -1.0), color: (1.0, 1.0, 1.0) },
+    DirectionalLight { direction: (-0.6, 0.6, -0.5), color: (1.0, 0.85, 0.6) },
+    DirectionalLight { direction: (0.7, -0.2, -0.6), color: (0.5, 0.6, 1.0) },
+    DirectionalLight { direction: (0.0, 0.5, 1.0), color: (0.8, 0.8, 0.8) },
+];
+
+/// A light infinitely far away, shining uniformly in one direction.
+#[derive(Clone, Copy)]
+pub struct DirectionalLight {
+    /// Direction from the surface towards the light, in camera space.
+    pub direction: (f32, f32, f32),
+    /// RGB color of the light, each channel in `[0, 1]`.
+    pub color: (f32, f32, f32),
+}
+
+impl DirectionalLight {
+    /// Returns the preset light for slot `index` (see `MAX_LIGHTS`).
+    pub fn preset(index: usize) -> DirectionalLight {
+        PRESETS[index % MAX_LIGHTS]
+    }
+}
+
+/// Ambient + diffuse (Lambert) + specular (Phong) lighting coefficients.
+pub struct Lighting {
+    /// Constant term added to every face, so faces turned away aren't pure black.
+    pub ambient: f32,
+    /// Weight of the \( \max(0, n \cdot l) \) term.
+    pub diffuse: f32,
+    /// Weight of the \( \max(0, r \cdot v)^{s} \) highlight; 0 disables it.
+    pub specular: f32,
+    /// Phong exponent \( s \): higher values give smaller, sharper highlights.
+    pub shininess: f32,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Lighting { ambient: 0.2, diffuse: 0.8, specular: 0.0, shininess: 32.0 }
+    }
+}
+
+impl Lighting {
+    /// Per-channel light reaching a surface with the (unit, camera-space)
+    /// `normal`, seen along `view` (unit vector from the surface to the camera),
+    /// summed over all `lights`:
+    /// $$ I_c = \text{clamp}(k_a + \sum_i (k_d \max(0, n \cdot l_i) + k_s \max(0, r_i \cdot v)^s) \, c_i, 0, 1) $$
+    /// where \( r_i = 2 (n \cdot l_i) n - l_i \) is the light reflected about the normal.
+    pub fn shade(
+        &self,
+        normal: (f32, f32, f32),
+        view: (f32, f32, f32),
+        lights: &[DirectionalLight],
+    ) -> (f32, f32, f32) {
+        let mut rgb = (self.ambient, self.ambient, self.ambient);
+        let (normal, view) = (Vec3::from(normal), Vec3::from(view));
+
+        for light in lights {
+            let l = Vec3::from(light.direction).normalize();
+            let n_dot_l = normal.dot(l);
+            if n_dot_l <= 0.0 {
+                continue;
+            }
+
+            let mut k = self.diffuse * n_dot_l;
+            if self.specular > 0.0 {
+                let r = normal * (2.0 * n_dot_l) - l;
+                k += self.specular * r.dot(view).max(0.0).powf(self.shininess);
+            }
+
+            rgb.0 += k * light.color.0;
+            rgb.1 += k * light.color.1;
+            rgb.2 += k * light.color.2;
+        }
+
+        (rgb.0.clamp(0.0, 1.0), rgb.1.clamp(0.0, 1.0), rgb.2.clamp(0.0, 1.0))
+    }
+}
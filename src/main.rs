@@ -1,16 +1,36 @@
 //! 3D Cube Rotation with Mouse in Rust using minifb
 //! Author: M3str3
 
-mod transformations;
-mod rendering;
+#[cfg(feature = "zip")]
+mod archive;
+mod camera;
+mod cli;
+mod colormap;
+mod dxf;
+mod export;
+mod flythrough;
+mod histogram;
+mod lighting;
+mod model;
+mod obj;
+mod ply;
+mod reference;
+mod stl;
+mod thumbnail;
 
+use m3str3_cube::{rendering, transformations};
 use minifb::{Key, MouseButton, MouseMode, Window, WindowOptions};
 use native_dialog::FileDialog;
+use rayon::prelude::*;
+use camera::Camera;
+use flythrough::CameraPath;
+use histogram::Histogram;
+use model::{load_model, EdgeSource, Model, Morph};
+use reference::ReferenceImage;
+use transformations::{Point3, Vec3};
 use std::{
-    fs::File,
-    io::{BufRead, BufReader},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
     env,
 };
 
@@ -22,100 +42,560 @@ const WHITE: u32 = 0xFFFFFFFF;
 const RED: u32 = 0xFF0000;
 const GREEN: u32 = 0x00FF00;
 const BLUE: u32 = 0x0000FF;
+const TEXT_LINE_HEIGHT: usize = 10; // Pixels between overlay text lines
+const SPECULAR: f32 = 0.6; // Highlight weight when specular is enabled
+const NORMAL_COLOR: u32 = 0xFF00FF; // Magenta, visible on every palette color
+const MAX_EDGE_STRIDE: usize = 8; // Edge stride cycles 1..=8 and wraps around
+const MAX_LINE_WIDTH: usize = 8; // Line width cycles 1..=8 px and wraps around
+const DEGENERATE_AREA_RATIO: f32 = 1e-6; // Faces below this fraction of the squared bounding-box diagonal
+const CLIP_BOX_COLOR: u32 = 0xFF8800; // Orange outline of the clipping box
+const HULL_COLOR: u32 = 0x00FFFF; // Cyan convex hull cage
+const GROUND_GRID_LINES: usize = 20; // Ground grid cells per side
+const GROUND_GRID_ALPHA: f32 = 0.25; // Ground grid color, from the background towards the text color
+const CLIP_FACES: [&str; 6] = ["-X", "+X", "-Y", "+Y", "-Z", "+Z"];
 
-const COLORS: [u32; 5] = [BLACK, WHITE, RED, GREEN, BLUE]; 
-const WIDTH: usize = 1000;
-const HEIGHT: usize = 800;
-const SCALE: f32 = 600.0; // Scaling factor for the 3D model in screen space
-const FRAME_DELAY_MS: u64 = 16; // ~60 fps (16 ms per frame)
+const COLORS: [u32; 5] = [BLACK, WHITE, RED, GREEN, BLUE]; // Default palette cycled by B and M (see --palette)
+const FRAME_DELAY_MS: u64 = 16; // ~60 fps (frames are padded to at least 16 ms)
+const WINDOW_TITLE: &str = "M3str3 - Model viewer";
+const TITLE_INTERVAL_MS: u64 = 1000; // How often the frame rate in the title is refreshed
+const FRAME_TIME_SMOOTHING: f32 = 0.1; // Weight of the newest frame in the smoothed frame times
+const GRID_DIVISIONS: [u32; 6] = [4, 8, 16, 32, 64, 128]; // Snapping grid sizes, per bounding-box diagonal
+const HISTOGRAM_BUCKETS: usize = 20; // Buckets of the edge-length histogram
+const HISTOGRAM_SIZE: (usize, usize) = (200, 80); // Plot area of the histogram in pixels
+const COMPASS_RADIUS: usize = 20; // Radius of the compass dial in pixels
+const TIMELINE_MARGIN: (usize, usize) = (16, 24); // Rotation timeline inset from the sides and the bottom
+const TIMELINE_STEP: f32 = 0.005; // Radians per frame when scrubbing the timeline with the arrow keys
+const SCALE_BAR_MAX_PIXELS: f32 = 150.0; // Longest scale bar; its length is rounded down from this
+const SLAB_STEP: f32 = 0.005; // Depth slab movement per frame, as a fraction of the model's depth
+const SLAB_THICKNESS: (f32, f32) = (0.01, 0.1); // Thinnest and initial depth slab, as a fraction of the model's depth
+const LENS_SHIFT_STEP: f32 = 0.005; // Lens shift per frame, as a fraction of the screen height
+const DOLLY_FACTOR: f32 = 0.9; // Distance change per scroll wheel step
+const MAX_FRAME_TIME: f32 = 0.1; // Longest frame time animations advance by, so a stall doesn't jump
+const DRAG_SENSITIVITY: f32 = 0.01; // Radians per pixel of mouse drag (per pixel, so independent of the frame rate)
+const LIGHT_SWEEP_STEP: f32 = 0.02; // Radians per frame the lights orbit during a light sweep
+const OCCLUSION_RADIUS: f32 = 0.05; // Neighborhood searched for crowded edges, per bounding-box diagonal
+const OCCLUSION_STRENGTH: f32 = 0.6; // Darkening of the most crowded edges
+const HOVER_RADIUS: f32 = 8.0; // Pixels from the mouse within which a vertex is labeled
+const MAX_INDEX_LABELS: usize = 500; // Largest model whose vertices are labeled with their indices
+const MIN_DISTANCE: f32 = 0.1; // Closest camera distance for empty models
+const DEFAULT_DISTANCE: f32 = 8.0; // Camera distance for empty models
+const FIT_DISTANCE: f32 = 4.62; // Camera distance per bounding radius on load (the default cube at 8)
+const WALK_SPEED: f32 = 0.005; // First-person step per frame, as a fraction of the model diagonal
+const MIN_NEAR: f32 = 1e-4; // Closest allowed near plane
+const MAX_NEAR: f32 = 10.0; // Farthest allowed near plane
+const EXPLODE_STEP: f32 = 0.002; // Explode offset per frame, per bounding-box diagonal
+const MAX_EXPLODE: f32 = 0.25; // Largest explode offset, per bounding-box diagonal
 
-/// 3D model structure: stores vertices and edges.
-struct Model {
-    vertices: Vec<(f32, f32, f32)>,
-    edges: Vec<(usize, usize)>,
+/// Number keys used for view bookmarks (slot `i` is `BOOKMARK_KEYS[i]`).
+const BOOKMARK_KEYS: [Key; 10] = [
+    Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4,
+    Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9,
+];
+
+/// A saved viewpoint, restored with its number key.
+#[derive(Clone, Copy)]
+struct Bookmark {
+    camera: Camera,
 }
 
-/// Loads a 3D model from a Wavefront `.obj` file.
-/// ---------------------------------------------------------------------
-/// Each line starting with `v` defines a vertex (`v x y z`).
-/// Each line starting with `f` defines a face (`f v1 v2 v3 [v4 ...]`).
-/// Indices in `.obj` are 1-based, so we shift them to 0-based for Rust.
-fn load_obj(file_path: &str) -> Result<Model, String> {
-    let file = File::open(file_path)
-        .map_err(|e| format!("Could not open file: {}", e))?;
-    let reader = BufReader::new(file);
-
-    let mut vertices = Vec::new();
-    let mut edges = Vec::new();
-
-    for line in reader.lines() {
-        let line = line.unwrap();
-
-        // Strip comments
-        let line = line.split('#').next().unwrap().trim();
-        if line.is_empty() {
-            continue;
+/// Winding that marks a face as facing the camera, for back-face culling.
+#[derive(Clone, Copy, Debug)]
+enum FrontFace {
+    /// Counterclockwise as seen from the front (OpenGL, Blender; the default).
+    Ccw,
+    /// Clockwise as seen from the front (Direct3D, some CAD exporters).
+    Cw,
+}
+
+impl FrontFace {
+    fn toggle(self) -> FrontFace {
+        match self {
+            FrontFace::Ccw => FrontFace::Cw,
+            FrontFace::Cw => FrontFace::Ccw,
         }
+    }
 
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.is_empty() {
-            continue;
+    /// Whether a face whose (counterclockwise) normal has dot product
+    /// `facing` with the view vector is front-facing.
+    fn is_front(self, facing: f32) -> bool {
+        match self {
+            FrontFace::Ccw => facing > 0.0,
+            FrontFace::Cw => facing < 0.0,
+        }
+    }
+}
+
+/// How the view is navigated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CameraMode {
+    /// The camera circles a target point (the origin unless moved), dragging
+    /// rotates the model.
+    Orbit,
+    /// The camera stands at an eye point, WASD walks it and dragging looks around.
+    FirstPerson,
+}
+
+/// Axes turned by auto-rotation.
+#[derive(Clone, Copy, Debug)]
+enum RotateAxes {
+    /// Y only, spinning the model upright like on a turntable (the default).
+    Turntable,
+    /// X and Y together, a diagonal tumble.
+    Tumble,
+    /// X only, flipping the model head over heels.
+    Flip,
+}
+
+impl RotateAxes {
+    fn next(self) -> RotateAxes {
+        match self {
+            RotateAxes::Turntable => RotateAxes::Tumble,
+            RotateAxes::Tumble => RotateAxes::Flip,
+            RotateAxes::Flip => RotateAxes::Turntable,
+        }
+    }
+
+    /// Increments of `(pitch, yaw)` turning the axes by `angle` radians.
+    fn step(self, angle: f32) -> (f32, f32) {
+        match self {
+            RotateAxes::Turntable => (0.0, angle),
+            RotateAxes::Tumble => (angle, angle),
+            RotateAxes::Flip => (angle, 0.0),
+        }
+    }
+}
+
+/// Render flags set by the individual mode keys.
+#[derive(Clone, Copy, PartialEq)]
+struct ModeFlags {
+    silhouette: bool,
+    filled: bool,
+    shaded: bool,
+    area: bool,
+    depth: bool,
+    normals: bool,
+}
+
+/// Named combinations of the render flags, cycled with Tab and shown as
+/// panels of the quad layout.
+#[derive(Clone, Copy, PartialEq)]
+enum RenderMode {
+    Wireframe,
+    Filled,
+    Normals,
+    Silhouette,
+    Shaded,
+    Depth,
+    Area,
+}
+
+impl RenderMode {
+    /// Every mode, in the order Tab cycles through them.
+    const ALL: [RenderMode; 7] = [
+        RenderMode::Wireframe,
+        RenderMode::Filled,
+        RenderMode::Normals,
+        RenderMode::Silhouette,
+        RenderMode::Shaded,
+        RenderMode::Depth,
+        RenderMode::Area,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            RenderMode::Wireframe => "Wireframe",
+            RenderMode::Filled => "Filled",
+            RenderMode::Normals => "Normals",
+            RenderMode::Silhouette => "Silhouette",
+            RenderMode::Shaded => "Shaded",
+            RenderMode::Depth => "Depth",
+            RenderMode::Area => "Area",
+        }
+    }
+
+    fn flags(self) -> ModeFlags {
+        let wireframe = ModeFlags { silhouette: false, filled: false, shaded: false, area: false, depth: false, normals: false };
+        match self {
+            RenderMode::Wireframe => wireframe,
+            RenderMode::Filled => ModeFlags { filled: true, ..wireframe },
+            RenderMode::Normals => ModeFlags { normals: true, ..wireframe },
+            RenderMode::Silhouette => ModeFlags { silhouette: true, ..wireframe },
+            RenderMode::Shaded => ModeFlags { shaded: true, ..wireframe },
+            RenderMode::Depth => ModeFlags { depth: true, ..wireframe },
+            RenderMode::Area => ModeFlags { area: true, ..wireframe },
         }
+    }
+
+    /// The mode matching `flags`, ignoring the normals overlay if no mode
+    /// matches exactly (e.g. shaded with normals is reported as `Shaded`).
+    fn matching(flags: ModeFlags) -> Option<RenderMode> {
+        let without_normals = ModeFlags { normals: false, ..flags };
+        RenderMode::ALL
+            .into_iter()
+            .find(|m| m.flags() == flags)
+            .or_else(|| RenderMode::ALL.into_iter().find(|m| m.flags() == without_normals))
+    }
 
-        match parts[0] {
-            "v" => {
-                // Vertex line: v x y z
-                let x: f32 = parts[1].parse().unwrap();
-                let y: f32 = parts[2].parse().unwrap();
-                let z: f32 = parts[3].parse().unwrap();
-                vertices.push((x, y, z));
+    /// The mode `step` places after `self` in `ALL` (negative steps go back).
+    fn cycle(self, step: isize) -> RenderMode {
+        let i = RenderMode::ALL.iter().position(|&m| m == self).unwrap_or(0) as isize;
+        RenderMode::ALL[(i + step).rem_euclid(RenderMode::ALL.len() as isize) as usize]
+    }
+}
+
+/// Per-frame counts of what happened to the wireframe edges.
+#[derive(Default)]
+struct RenderStats {
+    /// Edges drawn (at least partly) on screen.
+    drawn: usize,
+    /// Edges entirely outside the clipping box.
+    clipped: usize,
+    /// Edges entirely behind the near plane.
+    culled: usize,
+    /// Edges projected entirely outside the window.
+    offscreen: usize,
+}
+
+/// Totals accumulated over the whole run, printed when the viewer closes.
+struct SessionStats {
+    started: Instant,
+    /// Model and morph target files loaded, in order.
+    files: Vec<String>,
+    frames: u64,
+    /// Most edges drawn in a single frame.
+    peak_edges: usize,
+}
+
+impl SessionStats {
+    fn new() -> Self {
+        SessionStats { started: Instant::now(), files: Vec::new(), frames: 0, peak_edges: 0 }
+    }
+
+    fn print_summary(&self) {
+        let seconds = self.started.elapsed().as_secs_f64();
+        println!("Session summary:");
+        if self.files.is_empty() {
+            println!("  Files loaded: none (default cube)");
+        } else {
+            println!("  Files loaded: {}", self.files.len());
+            for file in &self.files {
+                println!("    {}", file);
             }
-            "f" => {
-                // Face line: f v1 v2 v3 [v4 ...]
-                let face_indices: Result<Vec<usize>, _> = parts[1..]
-                    .iter()
-                    .map(|v_str| v_str.parse::<usize>().map(|idx| idx - 1))
-                    .collect();
+        }
+        println!("  Frames rendered: {} in {:.1}s", self.frames, seconds);
+        if seconds > 0.0 {
+            println!("  Average FPS: {:.1}", self.frames as f64 / seconds);
+        }
+        println!("  Peak edges drawn: {}", self.peak_edges);
+    }
+}
 
-                let face_indices = match face_indices {
-                    Ok(face_indices) => face_indices,
-                    Err(_) => {
-                        eprintln!("Error parsing face indices in line: {}", line);
-                        continue;
-                    }
-                };
+/// Smoothed frame times, measured with `Instant` around every frame.
+struct FrameTimer {
+    last_frame: Instant,
+    last_title: Instant,
+    /// Seconds between consecutive frames, including the frame delay.
+    frame_time: f32,
+    /// Seconds spent handling input and rendering, without the frame delay.
+    render_time: f32,
+}
 
-                // Check index range
-                if face_indices.iter().any(|&i| i >= vertices.len()) {
-                    eprintln!("Index out of range in line: {}", line);
-                    continue;
+impl FrameTimer {
+    fn new() -> Self {
+        let now = Instant::now();
+        FrameTimer { last_frame: now, last_title: now, frame_time: FRAME_DELAY_MS as f32 / 1000.0, render_time: 0.0 }
+    }
+
+    /// Records a frame whose work started at `started` and ended now, and
+    /// returns the real time since the previous frame, in seconds.
+    fn tick(&mut self, started: Instant) -> f32 {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_frame).as_secs_f32();
+        let render = now.duration_since(started).as_secs_f32();
+        self.last_frame = now;
+        self.frame_time += (elapsed - self.frame_time) * FRAME_TIME_SMOOTHING;
+        self.render_time += (render - self.render_time) * FRAME_TIME_SMOOTHING;
+        elapsed
+    }
+
+    /// True once every `TITLE_INTERVAL_MS`, when the title should be refreshed.
+    fn title_due(&mut self) -> bool {
+        let due = self.last_title.elapsed() >= Duration::from_millis(TITLE_INTERVAL_MS);
+        if due {
+            self.last_title = Instant::now();
+        }
+        due
+    }
+
+    fn fps(&self) -> f32 {
+        1.0 / self.frame_time.max(f32::EPSILON)
+    }
+}
+
+/// Summary lines about the model's size, for `--info` and the info overlay.
+fn model_info(model: &Model) -> Vec<String> {
+    let mut info = vec![
+        format!(
+            "Vertices: {} | Edges: {} | Faces: {}",
+            model.vertices.len(),
+            model.edges.len(),
+            model.faces.len()
+        ),
+        {
+            let bytes = model.memory_footprint();
+            format!("Memory: {} bytes ({:.2} MB)", bytes, bytes as f64 / (1024.0 * 1024.0))
+        },
+    ];
+    if !model.objects.is_empty() {
+        let names: Vec<&str> = model.objects.iter().map(|o| o.name.as_str()).collect();
+        info.push(format!("Objects: {} ({})", names.len(), names.join(", ")));
+    }
+    info
+}
+
+/// Half the diagonal of the model's bounding box: the radius of a sphere
+/// around the box center that encloses the model.
+fn bounding_radius(model: &Model) -> f32 {
+    model.diagonal() / 2.0
+}
+
+/// The world axes as colored segments from the origin, `length` long:
+/// X red, Y green and Z blue.
+fn axis_lines(length: f32) -> [(Point3, Point3, u32); 3] {
+    let origin = (0.0, 0.0, 0.0);
+    [
+        (origin, (length, 0.0, 0.0), RED),
+        (origin, (0.0, length, 0.0), GREEN),
+        (origin, (0.0, 0.0, length), BLUE),
+    ]
+}
+
+/// Lines of a square grid on the XZ plane at height `y`, centered on the
+/// origin: `cells` cells of `spacing` per side. Built separately from the
+/// model, so it never shows up in its counts, exports or measurements.
+fn ground_grid(cells: usize, spacing: f32, y: f32) -> Vec<(Point3, Point3)> {
+    let half = cells as f32 * spacing / 2.0;
+    (0..=cells)
+        .flat_map(|i| {
+            let offset = i as f32 * spacing - half;
+            [((offset, y, -half), (offset, y, half)), ((-half, y, offset), (half, y, offset))]
+        })
+        .collect()
+}
+
+/// Orbit target and camera distance framing the model: the center of its
+/// bounding box, seen from `FIT_DISTANCE` bounding radii away.
+fn fit_view(model: &Model) -> (Vec3, f32) {
+    let (min, max) = model.bounds();
+    let radius = bounding_radius(model);
+    ((min + max) * 0.5, if radius > 0.0 { radius * FIT_DISTANCE } else { DEFAULT_DISTANCE })
+}
+
+/// Zoom range suiting the model's size: from half to 50 times its
+/// bounding radius (the camera orbits the bounding box center).
+fn auto_zoom_limits(model: &Model) -> (f32, f32) {
+    let radius = bounding_radius(model);
+    if radius > 0.0 {
+        ((radius * 0.5).max(MIN_NEAR), radius * 50.0)
+    } else {
+        (MIN_DISTANCE, f32::MAX)
+    }
+}
+
+/// The largest "round" length (1, 2 or 5 times a power of ten) not above
+/// `max`, for labeling the scale bar.
+fn nice_length(max: f32) -> f32 {
+    let power = 10f32.powf(max.log10().floor());
+    [5.0, 2.0, 1.0].into_iter().map(|k| k * power).find(|&l| l <= max).unwrap_or(power)
+}
+
+/// Colors telling the `count` parts of a multi-object model apart: the
+/// palette from the object color on, skipping the background, repeating
+/// once it runs out.
+fn object_colors(colors: &[u32], obj_color: usize, bg_color: usize, count: usize) -> Vec<u32> {
+    let candidates: Vec<u32> = (0..colors.len())
+        .map(|i| (obj_color + i) % colors.len())
+        .filter(|&i| i != bg_color || colors.len() == 1)
+        .map(|i| colors[i])
+        .collect();
+    (0..count).map(|k| candidates[k % candidates.len()]).collect()
+}
+
+/// Index of `color` in the palette, appending it first if it isn't there.
+fn palette_index(colors: &mut Vec<u32>, color: u32) -> usize {
+    colors.iter().position(|&c| c & 0xFFFFFF == color & 0xFFFFFF).unwrap_or_else(|| {
+        colors.push(color);
+        colors.len() - 1
+    })
+}
+
+/// Applies `--orient-outward` to a freshly loaded model and reports the result.
+fn orient_outward(model: &mut Model) {
+    let flipped = model.orient_normals_outward();
+    if flipped > 0 {
+        println!("Oriented normals outward: {} of {} faces flipped", flipped, model.faces.len());
+    }
+}
+
+/// Recursively collects the files under `dir` with a model extension.
+fn collect_model_files(dir: &std::path::Path, files: &mut Vec<std::path::PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_model_files(&path, files)?;
+        } else if path
+            .to_str()
+            .and_then(model::extension_of)
+            .is_some_and(|ext| model::MODEL_EXTENSIONS.contains(&ext.as_str()))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Asks the user for a reference image to open.
+fn open_image_dialog() -> Option<std::path::PathBuf> {
+    FileDialog::new()
+        .add_filter("Images", &["png", "jpg", "jpeg"])
+        .show_open_single_file()
+        .unwrap()
+}
+
+/// Asks the user for a model file to open.
+fn open_model_dialog() -> Option<std::path::PathBuf> {
+    FileDialog::new()
+        .add_filter("3D models", &model::MODEL_EXTENSIONS)
+        .add_filter("Wavefront OBJ", &["obj"])
+        .add_filter("Stanford PLY", &["ply"])
+        .add_filter("DXF drawing", &["dxf"])
+        .add_filter("STL", &["stl"])
+        .add_filter("ZIP archive", &["zip"])
+        .show_open_single_file()
+        .unwrap()
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let options = match cli::parse_args(&args[1..]) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+    // With an integer --scale the frame is rendered smaller and upscaled, so
+    // the window keeps the requested size
+    let factor = cli::scale_factor(options.scale);
+    let (mut width, mut height) = ((options.width / factor).max(1), (options.height / factor).max(1));
+
+    // Batch validation (--validate): load every model under a directory and exit
+    if let Some(dir) = &options.validate_dir {
+        let mut files = Vec::new();
+        if let Err(err) = collect_model_files(std::path::Path::new(dir), &mut files) {
+            eprintln!("Error reading {}: {}", dir, err);
+            std::process::exit(2);
+        }
+        files.sort();
+
+        let mut failed = 0;
+        for file in &files {
+            let path = file.display().to_string();
+            match load_model(&path, options.curve_samples).and_then(|model| model.validate().map(|()| model)) {
+                Ok(model) => println!("PASS {} ({} vertices, {} edges)", path, model.vertices.len(), model.edges.len()),
+                Err(err) => {
+                    println!("FAIL {}: {}", path, err);
+                    failed += 1;
                 }
+            }
+        }
+        println!("{} file(s) checked: {} passed, {} failed", files.len(), files.len() - failed, failed);
+        std::process::exit(if failed > 0 { 1 } else { 0 });
+    }
+
+    // Batch thumbnails (--thumbnails --out): render every model in parallel and exit
+    if let (Some(dir), Some(out_dir)) = (&options.thumbnails_dir, &options.out_dir) {
+        let root = std::path::Path::new(dir);
+        let mut files = Vec::new();
+        if let Err(err) = collect_model_files(root, &mut files).and_then(|()| std::fs::create_dir_all(out_dir)) {
+            eprintln!("Error preparing thumbnails: {}", err);
+            std::process::exit(2);
+        }
+        files.sort();
+
+        let done = std::sync::atomic::AtomicUsize::new(0);
+        let failed: usize = files
+            .par_iter()
+            .map(|file| {
+                // Name each thumbnail after the model's path below the input directory
+                let relative = file.strip_prefix(root).unwrap_or(file).display().to_string();
+                let name = format!("{}.png", relative.replace(['/', '\\'], "_"));
+                let out_path = std::path::Path::new(out_dir).join(name);
 
-                // Build edges from consecutive vertex indices
-                for i in 0..face_indices.len() {
-                    let start = face_indices[i];
-                    let end = face_indices[(i + 1) % face_indices.len()];
-                    if !edges.contains(&(start, end)) && !edges.contains(&(end, start)) {
-                        edges.push((start, end));
+                let result = load_model(&file.display().to_string(), options.curve_samples).and_then(|model| {
+                    let size = options.thumbnail_size;
+                    let buffer = thumbnail::render(&model, size, options.thumbnail_angles, options.color.unwrap_or(COLORS[0]), options.background.unwrap_or(COLORS[1]));
+                    export::write_png(out_path.to_str().unwrap(), size, size, &buffer)
+                });
+                let n = done.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+                match result {
+                    Ok(()) => {
+                        println!("[{}/{}] {} -> {}", n, files.len(), relative, out_path.display());
+                        0
                     }
+                    Err(err) => {
+                        println!("[{}/{}] FAIL {}: {}", n, files.len(), relative, err);
+                        1
+                    }
+                }
+            })
+            .sum();
+        println!("{} thumbnail(s) written, {} failed", files.len() - failed, failed);
+        std::process::exit(if failed > 0 { 1 } else { 0 });
+    }
+
+    // Headless runs (--info, --dxf): report or convert and exit without opening a window
+    if options.info || options.dxf_path.is_some() {
+        let model = match &options.model_path {
+            Some(path) => match load_model(path, options.curve_samples) {
+                Ok(model) => model,
+                Err(err) => {
+                    eprintln!("Error loading model: {}", err);
+                    return;
                 }
+            },
+            None => Model::cube(),
+        };
+        if options.info {
+            for line in model_info(&model) {
+                println!("{}", line);
+            }
+        }
+        if let Some(dxf_path) = &options.dxf_path {
+            match export::write_dxf(dxf_path, &model) {
+                Ok(()) => println!("Model exported to {:?} ({} edges)", dxf_path, model.edges.len()),
+                Err(err) => eprintln!("Error exporting DXF: {}", err),
             }
-            _ => {}
         }
+        return;
     }
 
-    Ok(Model { vertices, edges })
-}
-
-fn main() {
-    let mut obj_color: usize = 0;
-    let mut bg_color: usize = 1;
+    // Colors cycled by B and M (--palette or the defaults), with the --bg and
+    // --color choices added
+    let mut colors = options.palette.clone().unwrap_or_else(|| COLORS.to_vec());
+    let mut obj_color = options.color.map_or(0, |color| palette_index(&mut colors, color));
+    let mut bg_color = options.background.map_or(1.min(colors.len() - 1), |color| palette_index(&mut colors, color));
 
     let mut window = match Window::new(
-        "M3str3 - Model viewer",
-        WIDTH,
-        HEIGHT,
-        WindowOptions::default(),
+        WINDOW_TITLE,
+        width,
+        height,
+        WindowOptions {
+            resize: true,
+            scale: options.scale,
+            scale_mode: options.scale_mode.unwrap_or(minifb::ScaleMode::Stretch),
+            ..WindowOptions::default()
+        },
     ) {
         Ok(win) => win,
         Err(e) => {
@@ -124,56 +604,212 @@ fn main() {
         }
     };
 
-    // A buffer of size WIDTH * HEIGHT for drawing
-    let mut buffer = vec![0u32; WIDTH * HEIGHT];
+    // A buffer of size width * height for drawing
+    let mut buffer = vec![0u32; width * height];
 
-    // Rotation angles around X, Y, Z
-    let mut angle_x = 0.0_f32;
-    let mut angle_y = 0.0_f32;
-    let angle_z = 0.0_f32;
-
-    let mut distance: f32 = 8.0; // Distance from the camera to the origin
+    // Orbit or first-person navigation (Shift + V switches). The camera looks at
+    // the target from `distance` away (both set once the model is loaded); in
+    // first person the target is kept `distance` ahead of the `eye` instead of
+    // the eye circling the target.
+    let mut camera_mode = CameraMode::Orbit;
+    let mut eye = Vec3::default();
+    let mut lens_shift: (f32, f32) = (0.0, 0.0); // Projection center offset, in screen heights
+    let mut near = options.near; // Near-plane distance, Ctrl + PageUp / PageDown adjust it
     let mut auto_rotate = true;
+    let mut orthographic = false; // Orthographic instead of perspective projection (Ctrl + O)
+    let mut show_timeline = false; // Timeline to scrub the Y angle (Ctrl + Space)
+    let mut rotate_axes = RotateAxes::Turntable;
+
+    // Wireframe opacity, blended in sRGB or (gamma-correct) linear space
+    let mut blend = rendering::Blend { alpha: 1.0, linear: false };
+
+    // Crevice darkening: edges in crowded regions are drawn darker (S)
+    let mut edge_occlusion = false;
+
+    // Depth-based alpha: edges in the far half of the model fade from the near
+    // to the far opacity (Ctrl + Q / Ctrl + Shift + Q adjust the far one)
+    let mut depth_alpha = false;
+    let (near_alpha, mut far_alpha) = options.depth_alpha;
+
+    // Stippled (dotted) wireframe style
+    let mut stippled = false;
+    let mut stipple = rendering::Stipple { spacing: 6.0, jitter: 0.0 };
+
+    // Color edges by the per-vertex intensity stored in the model (PLY scans)
+    let mut intensity_mode = false;
+    let mut colormap_scheme = colormap::Scheme::Viridis;
+
+    // Which edges form the wireframe: face boundaries, explicit lines or both
+    let mut edge_source = EdgeSource::Combined;
+    let mut show_diagonals = false; // Triangulation diagonals of polygons
+    let mut edge_stride: usize = 1; // Draw only every Nth wireframe edge
+    let mut line_width = options.line_width; // Wireframe thickness in pixels, for high-DPI displays
+    let mut subpixel_lines = false; // Keep the projected endpoints' fractions, so slow motion doesn't jitter
+    let mut msaa = false; // Anti-alias the wireframe by sample coverage
+    let mut msaa_pattern = options.msaa_pattern; // Sample positions of the MSAA wireframe
+    let mut smooth_lines = false; // Draw the wireframe with Xiaolin Wu's anti-aliased lines
+
+    // Silhouette: fill the union of all projected faces in the object color
+    let mut silhouette = false;
+    // Convex hull cage (Shift + H), rebuilt whenever the vertices change
+    let mut show_hull = false;
+    let mut hull: Option<(Vec<Point3>, Model)> = None;
+    // World axes from the origin (Ctrl + X), as long as the model's bounding radius
+    let mut show_axes = false;
+    // Ground grid under the model (Ctrl + G), its spacing rounded from the model's size
+    let mut show_ground = false;
+
+    // Filled faces in their own colors, unlit, instead of the wireframe
+    let mut filled = false;
+
+    // Shaded faces: flat ambient + diffuse lighting from up to MAX_LIGHTS lights
+    let mut shaded = false;
+    let mut lighting = lighting::Lighting::default();
+    let mut lights = vec![lighting::DirectionalLight::preset(0)];
+    let mut light_sweep = false; // Orbit the lights every frame (Shift + ;)
+
+    // Back-face culling of filled faces, with a switchable front-face winding
+    let mut cull_back_faces = false;
+    let mut front_face = FrontFace::Ccw;
+
+    // Axis-aligned clipping box in model space; only geometry inside is drawn
+    let mut clip_box: Option<transformations::Aabb> = None;
+    let mut clip_face: usize = 0; // Index into CLIP_FACES of the face moved by PageUp/PageDown
+
+    // View-space depth slab: only edges whose mean depth lies inside are drawn.
+    // Center and thickness as fractions of the model's depth, front (0) to back (1)
+    let mut depth_slab: Option<(f32, f32)> = None;
+
+    // Morph between the model and a second one with the same topology
+    let mut morph: Option<Morph> = None;
+    let mut morph_t: f32 = 0.0; // Blend factor, 0 = model, 1 = target
+    let mut morph_playing = false;
+    let mut morph_phase: f32 = 0.0;
+
+    // View bookmarks, saved with Ctrl + number and recalled with the number
+    let mut bookmarks: [Option<Bookmark>; 10] = [None; 10];
+
+    // Flythrough: V plays the --camera-path keyframes in real time, or when
+    // saving each frame to --frames, one FRAME_DELAY_MS step per saved frame
+    let camera_path = options.camera_path.as_ref().and_then(|path| match CameraPath::load(path) {
+        Ok(camera_path) => {
+            println!("Camera path loaded: {:?} ({:.1} s), press V to play it", path, camera_path.duration());
+            Some(camera_path)
+        }
+        Err(err) => {
+            eprintln!("Error loading camera path: {}", err);
+            None
+        }
+    });
+    let mut flythrough_time: Option<f32> = None;
+    let mut frame_number: usize = 0;
+
+    // Single-line summary of the active modes at the bottom of the window
+    let mut show_status = true;
+
+    // Compass with the view's heading and pitch in the top-right corner
+    let mut show_compass = false;
+
+    // Overlay with per-frame edge counters (drawn, clipped, culled, off-screen)
+    let mut show_stats = false;
+
+    // Overlay with the model's element counts and memory footprint
+    let mut show_info = false;
+
+    // Edge-length histogram, computed when shown and dropped whenever a new
+    // model is loaded or the grid snapping changes
+    let mut show_histogram = false;
+    let mut edge_histogram: Option<Histogram> = None;
+
+    // Per-edge colors from the faces and vertices, computed once per model;
+    // the exploded copy has edges of its own, so they are also recomputed
+    // when the model starts or stops being exploded
+    let mut edge_color_cache: Option<(bool, Vec<Option<u32>>)> = None;
+    // Edge crowding for the occlusion-like darkening, rebuilt whenever the
+    // drawn vertices change
+    let mut edge_density_cache: Option<(Vec<Point3>, Vec<f32>)> = None;
+
+    // Reference image drawn behind the model: F8 shows/hides it, Ctrl + F8
+    // loads one, Shift + F8 clears it; Ctrl + [ / ] set its opacity and
+    // Ctrl + , / . its scale
+    let mut reference: Option<ReferenceImage> = options.reference_path.as_ref().and_then(|path| {
+        ReferenceImage::load(path)
+            .map_err(|err| eprintln!("Error loading reference image: {}", err))
+            .ok()
+    });
+    let mut show_reference = true;
+    let mut reference_blend = rendering::Blend { alpha: 0.5, linear: false };
+    let mut reference_scale: f32 = 1.0;
+
+    // Measurements HUD: bounding-box width, height and depth in model units
+    let mut show_measurements = false;
+    // Scale bar: how many model units a bar of pixels spans at the current zoom
+    let mut show_scale_bar = false;
+
+    // Explode along normals: W plays/pauses the animation, Shift + W resets it.
+    // The offset is a fraction of the model's bounding-box diagonal.
+    let mut explode: f32 = 0.0;
+    let mut exploding = false;
+    let mut explode_step = EXPLODE_STEP;
+
+    // Grid snapping: display vertices rounded to a grid of 1/N of the
+    // bounding-box diagonal (Ctrl + R applies it to the model)
+    let mut snap_to_grid = false;
+    let mut grid_division: usize = 2;
+
+    // Reverse every face's winding, for meshes exported inside out
+    let mut flipped_winding = false;
+
+    // Depth heatmap: color faces by their distance from the camera
+    let mut depth_mode = false;
+
+    // Quad layout: wireframe, shaded, depth heatmap and normals side by side
+    let mut quad_layout = false;
+
+    // Side-by-side stereo: left and right eye views in the window halves. The
+    // eye separation and the convergence distance (where the two views line
+    // up) are fractions of the camera distance.
+    let mut stereo = false;
+    let mut eye_separation: f32 = 0.06;
+    let mut convergence: f32 = 1.0;
+
+    // Color faces by area to spot slivers and degenerate (zero-area) faces
+    let mut area_mode = false;
+
+    // Normals "hedgehog": draw every Nth face (or vertex) normal as a short segment
+    let mut show_normals = false;
+    let mut use_vertex_normals = false;
+    let mut normal_stride: usize = 1;
+    let mut normal_length: f32 = 0.25;
 
     // Default 3D model, a cube with 8 vertices and 12 edges
-    let mut model = Model {
-        vertices: vec![
-            (-1.0, -1.0, -1.0),
-            ( 1.0, -1.0, -1.0),
-            ( 1.0,  1.0, -1.0),
-            (-1.0,  1.0, -1.0),
-            (-1.0, -1.0,  1.0),
-            ( 1.0, -1.0,  1.0),
-            ( 1.0,  1.0,  1.0),
-            (-1.0,  1.0,  1.0),
-        ],
-        edges: vec![
-            // Bottom face
-            (0, 1),
-            (1, 2),
-            (2, 3),
-            (3, 0),
-            // Top face
-            (4, 5),
-            (5, 6),
-            (6, 7),
-            (7, 4),
-            // Vertical edges
-            (0, 4),
-            (1, 5),
-            (2, 6),
-            (3, 7),
-        ],
-    };
+    let mut model = Model::cube();
+    // Loaded models are centered on the origin (and with --normalize scaled to
+    // unit size): how far they were moved and the scale applied after that
+    let mut model_offset = Vec3::default();
+    let mut model_scale: f32 = 1.0;
+
+    let mut session = SessionStats::new();
+    let mut frame_timer = FrameTimer::new();
+    let mut frame_time = FRAME_DELAY_MS as f32 / 1000.0; // Real duration of the previous frame, in seconds
 
     // Load argument at start
-    let args: Vec<String> = env::args().collect();
-    if args.len() > 1 {
-        let obj_file_path = &args[1];
-        match load_obj(obj_file_path) {
+    if let Some(obj_file_path) = &options.model_path {
+        match load_model(obj_file_path, options.curve_samples) {
             Ok(loaded_model) => {
                 model = loaded_model;
+                model_offset = model.center();
+                if options.normalize {
+                    model_scale = model.normalize_scale();
+                }
+                if options.orient_outward {
+                    orient_outward(&mut model);
+                }
+                session.files.push(obj_file_path.clone());
                 println!("Model loaded successfully: {:?}", obj_file_path);
+                if !model.intensity.is_empty() {
+                    println!("Model has per-vertex intensity, press I to display it");
+                }
             }
             Err(err) => {
                 eprintln!("Error loading model: {}", err);
@@ -181,138 +817,1824 @@ fn main() {
         }
     }
 
+    // Closest and farthest camera distance, derived from each loaded model,
+    // which is framed by orbiting its center
+    let mut zoom_limits = auto_zoom_limits(&model);
+    let (target, distance) = fit_view(&model);
+    let mut camera = Camera::new(target, distance, options.fov.to_radians());
+
     
     let mut last_mouse_pos: Option<(f32, f32)> = None;
+    let mut show_hover = false; // Label the vertex under the mouse (F12)
+    let mut show_indices = false; // Label every vertex with its index (Shift + F12)
 
     //////////////////////////////////////////////////////////////////////////////////////////
     // Main loop                                                                            //
     //////////////////////////////////////////////////////////////////////////////////////////
     while window.is_open() && !window.is_key_down(Key::Escape) {
+        // Unfocused: keep the last frame on screen and only poll the window at
+        // the background rate (a playing flythrough keeps rendering)
+        if !window.is_active() && flythrough_time.is_none() {
+            window.update();
+            thread::sleep(Duration::from_millis(1000 / options.background_fps as u64));
+            continue;
+        }
+        let frame_start = Instant::now();
+
+        // Without --scale-mode the frame buffer follows the window's size, so a
+        // resized window shows more (or less) of the scene instead of scaling it
+        if options.scale_mode.is_none() {
+            let (window_width, window_height) = window.get_size();
+            let size = ((window_width / factor).max(1), (window_height / factor).max(1));
+            if size != (width, height) {
+                (width, height) = size;
+                buffer = vec![0u32; width * height];
+            }
+        }
+
         // Clear the buffer to black
-        buffer.fill(COLORS[bg_color]);
+        buffer.fill((colors[bg_color] & 0xFFFFFF) | rendering::BACKGROUND_TAG);
+        let mut frame_edges: usize = 0;
 
         //////////////////////////////////////////////////////////////////////////////////////
         // Keyboard controls 
         //////////////////////////////////////////////////////////////////////////////////////
-        // Zoom in
-        if window.is_key_down(Key::Up) || window.is_key_down(Key::Equal) {
-            distance -= 0.1;
-            if distance < 0.1 {
-                distance = 0.1;
+        let shift = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+        let ctrl = window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl);
+        let alt = window.is_key_down(Key::LeftAlt) || window.is_key_down(Key::RightAlt);
+
+        // Lens shift: Ctrl + arrows move the projection center, Backspace recenters it
+        if ctrl {
+            if window.is_key_down(Key::Left) {
+                lens_shift.0 -= LENS_SHIFT_STEP;
+            }
+            if window.is_key_down(Key::Right) {
+                lens_shift.0 += LENS_SHIFT_STEP;
+            }
+            if window.is_key_down(Key::Up) {
+                lens_shift.1 -= LENS_SHIFT_STEP;
+            }
+            if window.is_key_down(Key::Down) {
+                lens_shift.1 += LENS_SHIFT_STEP;
+            }
+        }
+        if window.is_key_pressed(Key::Backspace, minifb::KeyRepeat::No) {
+            lens_shift = (0.0, 0.0);
+            println!("Lens shift: RESET");
+        }
+
+        // Zoom limits: Ctrl + - / = halve / double the closest distance, with
+        // Shift the farthest one
+        if ctrl {
+            let factor = if window.is_key_pressed(Key::Minus, minifb::KeyRepeat::Yes) {
+                0.5
+            } else if window.is_key_pressed(Key::Equal, minifb::KeyRepeat::Yes) {
+                2.0
+            } else {
+                1.0
+            };
+            if factor != 1.0 {
+                if shift {
+                    zoom_limits.1 = (zoom_limits.1 * factor).max(zoom_limits.0);
+                } else {
+                    zoom_limits.0 = (zoom_limits.0 * factor).max(MIN_NEAR).min(zoom_limits.1);
+                }
+                println!("Zoom limits: {:.3} to {:.3}", zoom_limits.0, zoom_limits.1);
             }
         }
+
+        // Zoom in
+        if !ctrl && (window.is_key_down(Key::Up) || window.is_key_down(Key::Equal)) {
+            camera.distance -= 0.1;
+        }
         // Zoom out
-        if window.is_key_down(Key::Down) || window.is_key_down(Key::Minus) {
-            distance += 0.1;
+        if !ctrl && (window.is_key_down(Key::Down) || window.is_key_down(Key::Minus)) {
+            camera.distance += 0.1;
         }
+        // The scroll wheel dollies in and out
+        if let Some((_, scroll)) = window.get_scroll_wheel() {
+            camera.distance *= DOLLY_FACTOR.powf(scroll);
+        }
+        camera.distance = camera.distance.clamp(zoom_limits.0, zoom_limits.1);
 
         // Change background color
         if window.is_key_pressed(Key::B, minifb::KeyRepeat::No) {
             bg_color += 1;
-            if bg_color >= COLORS.len() {
+            if bg_color >= colors.len() {
                 bg_color = 0;
             }
-            println!("Background color: {}", COLORS[bg_color]);
+            println!("Background color: {}", colors[bg_color]);
         }
 
         // Change object color
         if window.is_key_pressed(Key::M, minifb::KeyRepeat::No) {
             obj_color += 1;
-            if obj_color >= COLORS.len() {
+            if obj_color >= colors.len() {
                 obj_color = 0;
             }
-            println!("Object color: {}", COLORS[obj_color]);
+            println!("Object color: {}", colors[obj_color]);
         }
 
-        // Toggle auto-rotation
+        // Toggle auto-rotation (Shift + Space cycles the axes it turns, Ctrl + Space
+        // shows the timeline to scrub it)
         if window.is_key_pressed(Key::Space, minifb::KeyRepeat::No) {
-            auto_rotate = !auto_rotate;
-            println!("Auto-rotation: {}", if auto_rotate { "ENABLED" } else { "DISABLED" });
+            if ctrl {
+                show_timeline = !show_timeline;
+                auto_rotate &= !show_timeline;
+                println!("Rotation timeline: {}", if show_timeline { "ENABLED" } else { "DISABLED" });
+            } else if shift {
+                rotate_axes = rotate_axes.next();
+                auto_rotate = true;
+                println!("Auto-rotation axes: {:?}", rotate_axes);
+            } else {
+                auto_rotate = !auto_rotate;
+                println!("Auto-rotation: {}", if auto_rotate { "ENABLED" } else { "DISABLED" });
+            }
         }
 
-        // If auto-rotation is enabled, increment angles each frame
-        if auto_rotate {
-            angle_y += 0.01;
-            angle_x += 0.01;
+        // Toggle stippled wireframe
+        if window.is_key_pressed(Key::P, minifb::KeyRepeat::No) {
+            stippled = !stippled;
+            println!("Stippled wireframe: {}", if stippled { "ENABLED" } else { "DISABLED" });
         }
 
-        //////////////////////////////////////////////////////////////////////////////////////
-        // Mouse control for manual rotation (left-click)
-        //////////////////////////////////////////////////////////////////////////////////////
-        if let Some(pos) = window.get_mouse_pos(MouseMode::Discard) {
-            if window.get_mouse_down(MouseButton::Left) {
-                if let Some((last_x, last_y)) = last_mouse_pos {
-                    let dx = pos.0 - last_x;
-                    let dy = pos.1 - last_y;
-                    // Adjust sensitivity here (e.g. 0.01)
-                    angle_y -= dx * 0.01;
-                    angle_x -= dy * 0.01;
-                }
+        // Stipple spacing ([ / ]) and jitter (Shift + [ / ])
+        if window.is_key_pressed(Key::LeftBracket, minifb::KeyRepeat::Yes) {
+            if ctrl {
+                reference_blend.alpha = (reference_blend.alpha - 0.1).max(0.0);
+                println!("Reference opacity: {:.0}%", reference_blend.alpha * 100.0);
+            } else if shift {
+                stipple.jitter = (stipple.jitter - 0.1).max(0.0);
+                println!("Stipple jitter: {:.1}", stipple.jitter);
+            } else {
+                stipple.spacing = (stipple.spacing - 1.0).max(2.0);
+                println!("Stipple spacing: {}", stipple.spacing);
+            }
+        }
+        if window.is_key_pressed(Key::RightBracket, minifb::KeyRepeat::Yes) {
+            if ctrl {
+                reference_blend.alpha = (reference_blend.alpha + 0.1).min(1.0);
+                println!("Reference opacity: {:.0}%", reference_blend.alpha * 100.0);
+            } else if shift {
+                stipple.jitter = (stipple.jitter + 0.1).min(1.0);
+                println!("Stipple jitter: {:.1}", stipple.jitter);
+            } else {
+                stipple.spacing += 1.0;
+                println!("Stipple spacing: {}", stipple.spacing);
             }
-            last_mouse_pos = Some(pos);
-        } else {
-            last_mouse_pos = None;
         }
 
-        //////////////////////////////////////////////////////////////////////////////////////
-        // Press 'L' to load a new model from file
-        //////////////////////////////////////////////////////////////////////////////////////
-        if window.is_key_pressed(Key::L, minifb::KeyRepeat::No) {
-            println!("Loading model from file...");
-            if let Some(path) = FileDialog::new()
-                .add_filter("Wavefront OBJ", &["obj"])
-                .show_open_single_file()
-                .unwrap()
-            {
-                match load_obj(path.to_str().unwrap()) {
-                    Ok(loaded_model) => {
-                        model = loaded_model;
-                        println!("Model loaded successfully: {:?}", path);
-                    }
-                    Err(err) => {
-                        eprintln!("Error loading model: {}", err);
-                    }
-                }
+        // Wireframe opacity (Q raises, Shift + Q lowers) and gamma-correct blending (G)
+        if window.is_key_pressed(Key::Q, minifb::KeyRepeat::Yes) {
+            let step = if shift { -0.1 } else { 0.1 };
+            if ctrl {
+                far_alpha = (far_alpha + step).clamp(0.0, 1.0);
+                println!("Far edge opacity: {:.1}", far_alpha);
             } else {
-                println!("No file was selected");
+                blend.alpha = (blend.alpha + step).clamp(0.1, 1.0);
+                println!("Wireframe opacity: {:.1}", blend.alpha);
             }
         }
+        // Ctrl + G toggles the ground grid instead
+        if window.is_key_pressed(Key::G, minifb::KeyRepeat::No) && ctrl {
+            show_ground = !show_ground;
+            println!("Ground grid: {}", if show_ground { "ENABLED" } else { "DISABLED" });
+        } else if window.is_key_pressed(Key::G, minifb::KeyRepeat::No) {
+            blend.linear = !blend.linear;
+            println!("Gamma-correct blending: {}", if blend.linear { "ENABLED" } else { "DISABLED" });
+        }
 
-        //////////////////////////////////////////////////////////////////////////////////////
-        // Drawing the 3D model
-        //////////////////////////////////////////////////////////////////////////////////////
-        // We rotate each vertex around X, Y, Z, then project it using a simple perspective:
-        //
-        // $$ x' = x \cos(\theta_x) + \dots $$
-        // $$ u = x' \frac{\text{SCALE}}{z' + \text{distance}} + \frac{\text{WIDTH}}{2} $$
-        // $$ v = -y' \frac{\text{SCALE}}{z' + \text{distance}} + \frac{\text{HEIGHT}}{2} $$
-        
-        for &(i1, i2) in &model.edges {
-            let (x1, y1, z1) = model.vertices[i1];
-            let (x2, y2, z2) = model.vertices[i2];
-
-            // Rotate each endpoint around X, Y, and Z
-            let (rx1, ry1, rz1) = {
-                let (tx, ty, tz) = transformations::rotate_x(x1, y1, z1, angle_x);
-                let (tx, ty, tz) = transformations::rotate_y(tx, ty, tz, angle_y);
-                transformations::rotate_z(tx, ty, tz, angle_z)
-            };
+        // Toggle intensity coloring (only available when the model carries it)
+        if window.is_key_pressed(Key::I, minifb::KeyRepeat::No) {
+            if shift {
+                colormap_scheme = colormap_scheme.next();
+                println!("Colormap: {:?}", colormap_scheme);
+            } else if model.intensity.is_empty() {
+                println!("Intensity coloring unavailable: the model has no per-vertex intensity");
+            } else {
+                intensity_mode = !intensity_mode;
+                println!("Intensity coloring: {}", if intensity_mode { "ENABLED" } else { "DISABLED" });
+            }
+        }
 
-            let (rx2, ry2, rz2) = {
-                let (tx, ty, tz) = transformations::rotate_x(x2, y2, z2, angle_x);
-                let (tx, ty, tz) = transformations::rotate_y(tx, ty, tz, angle_y);
-                transformations::rotate_z(tx, ty, tz, angle_z)
-            };
+        // Cycle the wireframe edge source
+        if window.is_key_pressed(Key::E, minifb::KeyRepeat::No) {
+            edge_source = edge_source.next();
+            println!("Edge source: {:?}", edge_source);
+        }
+
+        // Toggle silhouette mask
+        if window.is_key_pressed(Key::H, minifb::KeyRepeat::No) {
+            if shift {
+                show_hull = !show_hull;
+                println!("Convex hull: {}", if show_hull { "ENABLED" } else { "DISABLED" });
+            } else {
+                silhouette = !silhouette;
+                println!("Silhouette: {}", if silhouette { "ENABLED" } else { "DISABLED" });
+            }
+        }
 
-            if let (Some(start), Some(end)) = (
-                transformations::project_perspective(rx1, ry1, rz1, distance, SCALE, WIDTH, HEIGHT),
-                transformations::project_perspective(rx2, ry2, rz2, distance, SCALE, WIDTH, HEIGHT),
-            ) {
-                rendering::draw_line(&mut buffer, WIDTH, HEIGHT, start, end, COLORS[obj_color]);
+        // Toggle shaded faces (F), the specular highlight (Shift + F) or
+        // switch between the wireframe and filled faces (Alt + F)
+        if window.is_key_pressed(Key::F, minifb::KeyRepeat::No) {
+            if alt {
+                filled = !filled;
+                println!("Filled faces: {}", if filled { "ENABLED" } else { "DISABLED" });
+            } else if ctrl && shift {
+                front_face = front_face.toggle();
+                println!("Front faces: {:?}", front_face);
+            } else if ctrl {
+                cull_back_faces = !cull_back_faces;
+                println!("Back-face culling: {}", if cull_back_faces { "ENABLED" } else { "DISABLED" });
+            } else if shift {
+                lighting.specular = if lighting.specular > 0.0 { 0.0 } else { SPECULAR };
+                println!("Specular: {}", if lighting.specular > 0.0 { "ENABLED" } else { "DISABLED" });
+            } else {
+                shaded = !shaded;
+                println!("Shaded faces: {}", if shaded { "ENABLED" } else { "DISABLED" });
             }
         }
-        
-        window.update_with_buffer(&buffer, WIDTH, HEIGHT).unwrap();
-        thread::sleep(Duration::from_millis(FRAME_DELAY_MS));
-    }
+
+        // Shininess (Y raises, Shift + Y lowers)
+        if window.is_key_pressed(Key::Y, minifb::KeyRepeat::Yes) {
+            lighting.shininess = if shift { lighting.shininess / 1.25 } else { lighting.shininess * 1.25 };
+            lighting.shininess = lighting.shininess.clamp(1.0, 512.0);
+            println!("Shininess: {:.1}", lighting.shininess);
+        }
+
+        // Toggle face-area coloring
+        if window.is_key_pressed(Key::A, minifb::KeyRepeat::No) && camera_mode == CameraMode::Orbit {
+            area_mode = !area_mode;
+            println!("Face area coloring: {}", if area_mode { "ENABLED" } else { "DISABLED" });
+        }
+
+        // Ambient (Z) and diffuse (C) coefficients, Shift lowers them
+        if window.is_key_pressed(Key::Z, minifb::KeyRepeat::Yes) {
+            let step = if shift { -0.05 } else { 0.05 };
+            lighting.ambient = (lighting.ambient + step).clamp(0.0, 1.0);
+            println!("Ambient: {:.2}", lighting.ambient);
+        }
+        if window.is_key_pressed(Key::C, minifb::KeyRepeat::Yes) {
+            let step = if shift { -0.05 } else { 0.05 };
+            lighting.diffuse = (lighting.diffuse + step).clamp(0.0, 1.0);
+            println!("Diffuse: {:.2}", lighting.diffuse);
+        }
+
+        // Add (J) or remove (Shift + J) a directional light
+        if window.is_key_pressed(Key::J, minifb::KeyRepeat::No) {
+            if shift {
+                if lights.len() > 1 {
+                    lights.pop();
+                }
+            } else if lights.len() < lighting::MAX_LIGHTS {
+                lights.push(lighting::DirectionalLight::preset(lights.len()));
+            }
+            println!("Lights: {}", lights.len());
+        }
+
+        // Rotate all lights around the view's vertical axis, or with Shift + ;
+        // toggle sweeping them around continuously
+        if window.is_key_pressed(Key::Semicolon, minifb::KeyRepeat::No) && shift && !ctrl {
+            light_sweep = !light_sweep;
+            println!("Light sweep: {}", if light_sweep { "ENABLED" } else { "DISABLED" });
+        }
+        let mut light_step = if light_sweep { LIGHT_SWEEP_STEP } else { 0.0 };
+        for (key, step) in [(Key::Semicolon, -0.05), (Key::Apostrophe, 0.05)] {
+            if window.is_key_down(key) && !ctrl && !shift {
+                light_step += step;
+            }
+        }
+        if light_step != 0.0 {
+            for light in &mut lights {
+                let (x, y, z) = light.direction;
+                light.direction = transformations::rotate_y(x, y, z, light_step);
+            }
+        }
+
+        // Stereo: Ctrl + ; / ' narrow / widen the eye separation, with Shift they
+        // move the convergence distance closer / farther
+        for (key, factor) in [(Key::Semicolon, 1.0 / 1.25), (Key::Apostrophe, 1.25)] {
+            if ctrl && window.is_key_pressed(key, minifb::KeyRepeat::Yes) {
+                if shift {
+                    convergence = (convergence * factor).clamp(0.1, 10.0);
+                    println!("Stereo convergence: {:.2} x distance", convergence);
+                } else {
+                    eye_separation = (eye_separation * factor).clamp(0.001, 0.5);
+                    println!("Stereo eye separation: {:.3} x distance", eye_separation);
+                }
+            }
+        }
+
+        // Clipping box: \ toggles, Shift + \ selects a face, PageUp/PageDown move it.
+        // Ctrl + \ toggles the depth slab instead
+        if window.is_key_pressed(Key::Backslash, minifb::KeyRepeat::No) {
+            if ctrl {
+                depth_slab = match depth_slab {
+                    Some(_) => None,
+                    None => Some((0.5, SLAB_THICKNESS.1)),
+                };
+                println!("Depth slab: {}", if depth_slab.is_some() { "ENABLED" } else { "DISABLED" });
+            } else if shift {
+                clip_face = (clip_face + 1) % CLIP_FACES.len();
+                println!("Clipping box face: {}", CLIP_FACES[clip_face]);
+            } else if clip_box.is_some() {
+                clip_box = None;
+                println!("Clipping box: DISABLED");
+            } else {
+                let (min, max) = model.bounding_box();
+                clip_box = Some(transformations::Aabb { min, max });
+                println!("Clipping box: ENABLED");
+            }
+        }
+        // Near plane: Ctrl + PageUp / PageDown move it away from / towards the camera
+        if ctrl {
+            let factor = if window.is_key_pressed(Key::PageUp, minifb::KeyRepeat::Yes) {
+                2.0
+            } else if window.is_key_pressed(Key::PageDown, minifb::KeyRepeat::Yes) {
+                0.5
+            } else {
+                1.0
+            };
+            if factor != 1.0 {
+                near = (near * factor).clamp(MIN_NEAR, MAX_NEAR);
+                println!("Near plane: {}", near);
+            }
+        }
+        if let (Some(aabb), false) = (&mut clip_box, ctrl) {
+            let (min, max) = model.bounding_box();
+            let step = 0.02 * (max.0 - min.0).max(max.1 - min.1).max(max.2 - min.2).max(f32::EPSILON);
+            let outward = if clip_face.is_multiple_of(2) { -step } else { step };
+            let delta = if window.is_key_pressed(Key::PageUp, minifb::KeyRepeat::Yes) {
+                outward
+            } else if window.is_key_pressed(Key::PageDown, minifb::KeyRepeat::Yes) {
+                -outward
+            } else {
+                0.0
+            };
+
+            if delta != 0.0 {
+                let (bound, opposite) = match clip_face {
+                    0 => (&mut aabb.min.0, aabb.max.0),
+                    1 => (&mut aabb.max.0, aabb.min.0),
+                    2 => (&mut aabb.min.1, aabb.max.1),
+                    3 => (&mut aabb.max.1, aabb.min.1),
+                    4 => (&mut aabb.min.2, aabb.max.2),
+                    _ => (&mut aabb.max.2, aabb.min.2),
+                };
+                // Never let a face cross the opposite one
+                *bound += delta;
+                *bound = if clip_face.is_multiple_of(2) { bound.min(opposite) } else { bound.max(opposite) };
+            }
+        }
+
+        // Morph: U plays/pauses, Shift + U removes the target, Home / End scrub
+        if window.is_key_pressed(Key::U, minifb::KeyRepeat::No) {
+            if shift {
+                if let Some(old) = morph.take() {
+                    model.vertices.copy_from_slice(&old.source);
+                    println!("Morph: REMOVED");
+                }
+            } else if morph.is_some() {
+                morph_playing = !morph_playing;
+                println!("Morph animation: {}", if morph_playing { "PLAYING" } else { "PAUSED" });
+            } else {
+                println!("No morph target, press Shift + L to load one");
+            }
+        }
+        // Depth slab: Ctrl + Home / End slide it towards / away from the camera,
+        // with Shift they make it thinner / thicker
+        if let (Some((center, thickness)), true) = (&mut depth_slab, ctrl) {
+            let direction = match (window.is_key_down(Key::Home), window.is_key_down(Key::End)) {
+                (true, false) => -1.0,
+                (false, true) => 1.0,
+                _ => 0.0,
+            };
+            if shift {
+                *thickness = (*thickness + direction * SLAB_STEP).clamp(SLAB_THICKNESS.0, 1.0);
+            } else {
+                *center = (*center + direction * SLAB_STEP).clamp(0.0, 1.0);
+            }
+        }
+        if let (Some(active), false) = (&morph, ctrl) {
+            if window.is_key_down(Key::Home) {
+                morph_t = (morph_t - 0.01).max(0.0);
+                morph_playing = false;
+            }
+            if window.is_key_down(Key::End) {
+                morph_t = (morph_t + 0.01).min(1.0);
+                morph_playing = false;
+            }
+            if morph_playing {
+                // Ease back and forth between both shapes
+                morph_phase += 0.02;
+                morph_t = 0.5 - 0.5 * morph_phase.cos();
+            } else {
+                morph_phase = (1.0 - 2.0 * morph_t).acos();
+            }
+            active.apply(morph_t, &mut model.vertices);
+        }
+
+        // View bookmarks
+        for (slot, &key) in BOOKMARK_KEYS.iter().enumerate() {
+            if !window.is_key_pressed(key, minifb::KeyRepeat::No) {
+                continue;
+            }
+            if ctrl {
+                bookmarks[slot] = Some(Bookmark { camera });
+                println!("View saved to bookmark {}", slot);
+            } else if let Some(bookmark) = bookmarks[slot] {
+                camera = bookmark.camera;
+                auto_rotate = false;
+                println!("View restored from bookmark {}", slot);
+            } else {
+                println!("Bookmark {} is empty, press Ctrl + {} to save one", slot, slot);
+            }
+        }
+
+        // Switch between orbit and first-person navigation, keeping the viewpoint
+        if window.is_key_pressed(Key::V, minifb::KeyRepeat::No) && shift {
+            camera_mode = match camera_mode {
+                CameraMode::Orbit => {
+                    let (_, _, forward) = camera.axes();
+                    eye = camera.target - forward * camera.distance;
+                    auto_rotate = false;
+                    CameraMode::FirstPerson
+                }
+                CameraMode::FirstPerson => CameraMode::Orbit,
+            };
+            println!("Camera mode: {:?}", camera_mode);
+        }
+
+        // Play/stop the camera path flythrough
+        if window.is_key_pressed(Key::V, minifb::KeyRepeat::No) && !shift {
+            if camera_path.is_none() {
+                println!("No camera path loaded, start with --camera-path FILE");
+            } else if flythrough_time.take().is_some() {
+                println!("Flythrough: STOPPED");
+            } else {
+                flythrough_time = Some(0.0);
+                frame_number = 0;
+                if let Some(dir) = &options.frames_dir {
+                    if let Err(err) = std::fs::create_dir_all(dir) {
+                        eprintln!("Error creating frames directory: {}", err);
+                    }
+                }
+                println!("Flythrough: PLAYING");
+            }
+        }
+
+        // Grid snapping: R toggles, Shift + R cycles the grid size,
+        // Ctrl + R snaps the model itself
+        let grid_step = model.diagonal().max(f32::EPSILON) / GRID_DIVISIONS[grid_division] as f32;
+        if window.is_key_pressed(Key::R, minifb::KeyRepeat::No) {
+            // The histogram follows the displayed (possibly snapped) vertices
+            edge_histogram = None;
+            if ctrl {
+                model.snap_to_grid(grid_step);
+                snap_to_grid = false;
+                println!("Model snapped to a grid of {:.4}", grid_step);
+            } else if shift {
+                grid_division = (grid_division + 1) % GRID_DIVISIONS.len();
+                println!("Snapping grid: 1/{} of the model size", GRID_DIVISIONS[grid_division]);
+            } else {
+                snap_to_grid = !snap_to_grid;
+                println!("Grid snapping: {}", if snap_to_grid { "ENABLED" } else { "DISABLED" });
+            }
+        }
+
+        // Toggle the status bar
+        if window.is_key_pressed(Key::F1, minifb::KeyRepeat::No) {
+            if shift {
+                show_compass = !show_compass;
+                println!("Compass: {}", if show_compass { "ENABLED" } else { "DISABLED" });
+            } else {
+                show_status = !show_status;
+            }
+        }
+
+        // Toggle the render statistics overlay
+        if window.is_key_pressed(Key::F2, minifb::KeyRepeat::No) {
+            show_stats = !show_stats;
+            println!("Render statistics: {}", if show_stats { "ENABLED" } else { "DISABLED" });
+        }
+
+        // Toggle the depth heatmap
+        if window.is_key_pressed(Key::D, minifb::KeyRepeat::No) && camera_mode == CameraMode::Orbit {
+            depth_mode = !depth_mode;
+            println!("Depth heatmap: {}", if depth_mode { "ENABLED" } else { "DISABLED" });
+        }
+
+        // Cycle the named render modes (Shift + Tab goes back)
+        if window.is_key_pressed(Key::Tab, minifb::KeyRepeat::No) {
+            let flags = ModeFlags { silhouette, filled, shaded, area: area_mode, depth: depth_mode, normals: show_normals };
+            let mode = match RenderMode::matching(flags) {
+                Some(mode) => mode.cycle(if shift { -1 } else { 1 }),
+                None => RenderMode::Wireframe,
+            };
+            ModeFlags { silhouette, filled, shaded, area: area_mode, depth: depth_mode, normals: show_normals } = mode.flags();
+            println!("Render mode: {}", mode.name());
+        }
+
+        // Toggle the measurements HUD, or with Shift the scale bar
+        if window.is_key_pressed(Key::F5, minifb::KeyRepeat::No) {
+            if shift {
+                show_scale_bar = !show_scale_bar;
+                println!("Scale bar: {}", if show_scale_bar { "ENABLED" } else { "DISABLED" });
+            } else {
+                show_measurements = !show_measurements;
+                println!("Measurements: {}", if show_measurements { "ENABLED" } else { "DISABLED" });
+            }
+        }
+
+        // Toggle the edge-length histogram
+        if window.is_key_pressed(Key::F6, minifb::KeyRepeat::No) {
+            show_histogram = !show_histogram;
+            println!("Edge-length histogram: {}", if show_histogram { "ENABLED" } else { "DISABLED" });
+        }
+
+        // Toggle MSAA-style anti-aliasing of the wireframe, or with Shift cycle its sample pattern.
+        // Ctrl + F7 toggles Wu anti-aliased lines instead
+        if window.is_key_pressed(Key::F7, minifb::KeyRepeat::No) {
+            if ctrl {
+                smooth_lines = !smooth_lines;
+                println!("Smooth (Wu) lines: {}", if smooth_lines { "ENABLED" } else { "DISABLED" });
+            } else if shift {
+                msaa_pattern = msaa_pattern.next();
+                println!("MSAA samples: {}x", msaa_pattern.count());
+            } else {
+                msaa = !msaa;
+                println!("MSAA wireframe: {}", if msaa { "ENABLED" } else { "DISABLED" });
+            }
+        }
+
+        // Reference image: show/hide, load (Ctrl) or clear (Shift)
+        if window.is_key_pressed(Key::F8, minifb::KeyRepeat::No) {
+            if ctrl {
+                if let Some(path) = open_image_dialog() {
+                    match ReferenceImage::load(path.to_str().unwrap()) {
+                        Ok(image) => {
+                            reference = Some(image);
+                            show_reference = true;
+                            println!("Reference image loaded: {:?}", path);
+                        }
+                        Err(err) => eprintln!("Error loading reference image: {}", err),
+                    }
+                }
+            } else if shift {
+                reference = None;
+                println!("Reference image: CLEARED");
+            } else {
+                show_reference = !show_reference;
+                println!("Reference image: {}", if show_reference { "SHOWN" } else { "HIDDEN" });
+            }
+        }
+
+        // Toggle ambient-occlusion-like darkening of crowded edges
+        if window.is_key_pressed(Key::S, minifb::KeyRepeat::No) && camera_mode == CameraMode::Orbit {
+            edge_occlusion = !edge_occlusion;
+            println!("Edge occlusion: {}", if edge_occlusion { "ENABLED" } else { "DISABLED" });
+        }
+
+        // Toggle depth-based alpha (far edges fade towards the background)
+        if window.is_key_pressed(Key::F11, minifb::KeyRepeat::No) {
+            depth_alpha = !depth_alpha;
+            println!("Depth-based alpha: {}", if depth_alpha { "ENABLED" } else { "DISABLED" });
+        }
+
+        // Explode the faces along their normals
+        if window.is_key_pressed(Key::W, minifb::KeyRepeat::No) && camera_mode == CameraMode::Orbit {
+            if shift {
+                explode = 0.0;
+                exploding = false;
+                explode_step = EXPLODE_STEP;
+                println!("Explode: RESET");
+            } else {
+                exploding = !exploding;
+                println!("Explode animation: {}", if exploding { "PLAYING" } else { "PAUSED" });
+            }
+        }
+        if exploding {
+            // Ping-pong between the assembled mesh and the fully exploded one
+            explode += explode_step;
+            if explode >= MAX_EXPLODE || explode <= 0.0 {
+                explode = explode.clamp(0.0, MAX_EXPLODE);
+                explode_step = -explode_step;
+            }
+        }
+
+        // Toggle the hovered-vertex coordinates label, or with Shift the vertex indices
+        if window.is_key_pressed(Key::F12, minifb::KeyRepeat::No) {
+            if shift {
+                show_indices = !show_indices;
+                println!("Vertex indices: {}", if show_indices { "ENABLED" } else { "DISABLED" });
+                if show_indices && model.vertices.len() > MAX_INDEX_LABELS {
+                    println!("The model has over {} vertices, indices are only shown for smaller models", MAX_INDEX_LABELS);
+                }
+            } else {
+                show_hover = !show_hover;
+                println!("Hovered vertex label: {}", if show_hover { "ENABLED" } else { "DISABLED" });
+            }
+        }
+
+        // Toggle the 2x2 viewport layout
+        if window.is_key_pressed(Key::F4, minifb::KeyRepeat::No) {
+            if shift {
+                stereo = !stereo;
+                println!("Stereo side by side: {}", if stereo { "ENABLED" } else { "DISABLED" });
+            } else {
+                quad_layout = !quad_layout;
+                println!("Quad layout: {}", if quad_layout { "ENABLED" } else { "DISABLED" });
+            }
+        }
+
+        // Toggle the model info overlay
+        if window.is_key_pressed(Key::F3, minifb::KeyRepeat::No) {
+            show_info = !show_info;
+            println!("Model info: {}", if show_info { "ENABLED" } else { "DISABLED" });
+        }
+
+        // Cycle the wireframe edge stride (every Nth edge)
+        if window.is_key_pressed(Key::K, minifb::KeyRepeat::No) {
+            edge_stride = if edge_stride >= MAX_EDGE_STRIDE { 1 } else { edge_stride + 1 };
+            println!("Edge stride: every {} edge(s)", edge_stride);
+        }
+
+        // Flip the winding (and so the normals) of all faces, or with Shift only
+        // the faces pointing towards the centroid
+        // Ctrl + X toggles the world axes
+        if window.is_key_pressed(Key::X, minifb::KeyRepeat::No) && ctrl {
+            show_axes = !show_axes;
+            println!("World axes: {}", if show_axes { "ENABLED" } else { "DISABLED" });
+        } else if window.is_key_pressed(Key::X, minifb::KeyRepeat::No) && shift {
+            let flipped = model.orient_normals_outward();
+            println!("Oriented normals outward: {} of {} faces flipped", flipped, model.faces.len());
+        } else if window.is_key_pressed(Key::X, minifb::KeyRepeat::No) {
+            flipped_winding = !flipped_winding;
+            model.flip_winding();
+            println!("Face winding: {}", if flipped_winding { "FLIPPED" } else { "ORIGINAL" });
+        }
+
+        // Cycle the wireframe line width, or with Shift toggle subpixel endpoints.
+        // Ctrl + O switches between perspective and orthographic projection.
+        if window.is_key_pressed(Key::O, minifb::KeyRepeat::No) {
+            if ctrl {
+                orthographic = !orthographic;
+                println!("Projection: {}", if orthographic { "ORTHOGRAPHIC" } else { "PERSPECTIVE" });
+            } else if shift {
+                subpixel_lines = !subpixel_lines;
+                println!("Subpixel lines: {}", if subpixel_lines { "ENABLED" } else { "DISABLED" });
+            } else {
+                line_width = if line_width >= MAX_LINE_WIDTH { 1 } else { line_width + 1 };
+                println!("Line width: {} px", line_width);
+            }
+        }
+
+        // Show/hide triangulation diagonals
+        if window.is_key_pressed(Key::T, minifb::KeyRepeat::No) {
+            show_diagonals = !show_diagonals;
+            println!("Triangulation diagonals: {}", if show_diagonals { "SHOWN" } else { "HIDDEN" });
+        }
+
+        // Normals hedgehog: N toggles, Shift + N switches face/vertex normals,
+        // , / . change the density and Shift + , / . change the length
+        if window.is_key_pressed(Key::N, minifb::KeyRepeat::No) {
+            if shift {
+                use_vertex_normals = !use_vertex_normals;
+                println!("Normals source: {}", if use_vertex_normals { "VERTEX" } else { "FACE" });
+            } else {
+                show_normals = !show_normals;
+                println!("Normals: {}", if show_normals { "ENABLED" } else { "DISABLED" });
+            }
+        }
+        if window.is_key_pressed(Key::Comma, minifb::KeyRepeat::Yes) {
+            if ctrl {
+                reference_scale = (reference_scale / 1.1).max(0.05);
+            } else if shift {
+                normal_length = (normal_length / 1.25).max(0.01);
+            } else {
+                normal_stride = normal_stride.saturating_sub(1).max(1);
+            }
+        }
+        if window.is_key_pressed(Key::Period, minifb::KeyRepeat::Yes) {
+            if ctrl {
+                reference_scale *= 1.1;
+            } else if shift {
+                normal_length *= 1.25;
+            } else {
+                normal_stride += 1;
+            }
+        }
+
+        // If auto-rotation is enabled, turn by the angular speed times the real
+        // frame time, so the model spins at the same rate however long frames take
+        if auto_rotate {
+            let (dx, dy) = rotate_axes.step(options.rotate_speed.to_radians() * frame_time);
+            camera.pitch += dx;
+            camera.yaw += dy;
+        }
+
+        // Scrub the timeline with the arrow keys (Shift scrubs faster)
+        if show_timeline && !ctrl {
+            let step = TIMELINE_STEP * if shift { 10.0 } else { 1.0 };
+            if window.is_key_down(Key::Left) {
+                camera.yaw -= step;
+                auto_rotate = false;
+            }
+            if window.is_key_down(Key::Right) {
+                camera.yaw += step;
+                auto_rotate = false;
+            }
+        }
+
+        // A playing flythrough drives the view
+        if let (Some(time), Some(path)) = (flythrough_time, &camera_path) {
+            (camera.pitch, camera.yaw, camera.distance) = path.sample(time);
+            auto_rotate = false;
+        }
+
+        //////////////////////////////////////////////////////////////////////////////////////
+        // Mouse control for manual rotation (left-click)
+        //////////////////////////////////////////////////////////////////////////////////////
+        // Height of the viewports the mouse works in
+        let view_height = if quad_layout && !stereo { height / 2 } else { height };
+        let timeline_y = height.saturating_sub(TIMELINE_MARGIN.1) as f32;
+        let timeline_width = width.saturating_sub(2 * TIMELINE_MARGIN.0).max(1) as f32;
+        if let Some(pos) = window.get_mouse_pos(MouseMode::Discard) {
+            if window.get_mouse_down(MouseButton::Left) {
+                if show_timeline && (pos.1 - timeline_y).abs() <= 8.0 {
+                    // Dragging along the timeline sets the Y angle directly
+                    let t = ((pos.0 - TIMELINE_MARGIN.0 as f32) / timeline_width).clamp(0.0, 1.0);
+                    camera.yaw = t * std::f32::consts::TAU;
+                    auto_rotate = false;
+                } else if let Some((last_x, last_y)) = last_mouse_pos {
+                    let dx = pos.0 - last_x;
+                    let dy = pos.1 - last_y;
+                    camera.yaw -= dx * DRAG_SENSITIVITY;
+                    camera.pitch -= dy * DRAG_SENSITIVITY;
+                }
+            } else if window.get_mouse_down(MouseButton::Middle) {
+                // Middle drag pans the target (and in first person the eye with it)
+                if let Some((last_x, last_y)) = last_mouse_pos {
+                    let before = camera.target;
+                    camera.pan((pos.0 - last_x, pos.1 - last_y), camera.scale(view_height));
+                    eye = eye + (camera.target - before);
+                }
+            }
+            last_mouse_pos = Some(pos);
+        } else {
+            last_mouse_pos = None;
+        }
+
+        // First person: WASD walks the eye (Shift runs) and the target follows,
+        // straight ahead of it
+        if camera_mode == CameraMode::FirstPerson {
+            let (right, _, forward) = camera.axes();
+
+            let diagonal = model.diagonal().max(MIN_DISTANCE);
+            let step = diagonal * WALK_SPEED * if shift { 4.0 } else { 1.0 };
+            for (key, direction) in [(Key::W, forward), (Key::S, forward * -1.0), (Key::D, right), (Key::A, right * -1.0)] {
+                if window.is_key_down(key) {
+                    eye = eye + direction * step;
+                }
+            }
+            camera.target = eye + forward * camera.distance;
+        }
+
+        //////////////////////////////////////////////////////////////////////////////////////
+        // Press 'L' to load a new model from file
+        // (Shift + L loads a morph target instead)
+        //////////////////////////////////////////////////////////////////////////////////////
+        if window.is_key_pressed(Key::L, minifb::KeyRepeat::No) && shift {
+            println!("Loading morph target from file...");
+            if let Some(path) = open_model_dialog() {
+                // Morph from the model's own shape, not the current blend
+                let base = Model {
+                    vertices: morph.as_ref().map_or_else(|| model.vertices.clone(), |m| m.source.clone()),
+                    edges: model.edges.clone(),
+                    ..Model::default()
+                };
+                // The target is moved along with the model, keeping their relative placement
+                let loaded = load_model(path.to_str().unwrap(), options.curve_samples).and_then(|mut target| {
+                    target.translate(model_offset * -1.0);
+                    target.scale(model_scale);
+                    Morph::new(&base, &target)
+                });
+                match loaded {
+                    Ok(new_morph) => {
+                        morph = Some(new_morph);
+                        morph_t = 0.0;
+                        morph_playing = true;
+                        session.files.push(path.display().to_string());
+                        println!("Morph target loaded: {:?}", path);
+                    }
+                    Err(err) => {
+                        eprintln!("Error loading morph target: {}", err);
+                    }
+                }
+            } else {
+                println!("No file was selected");
+            }
+        } else if window.is_key_pressed(Key::L, minifb::KeyRepeat::No) {
+            println!("Loading model from file...");
+            if let Some(path) = open_model_dialog() {
+                match load_model(path.to_str().unwrap(), options.curve_samples) {
+                    Ok(loaded_model) => {
+                        model = loaded_model;
+                        model_offset = model.center();
+                        model_scale = if options.normalize { model.normalize_scale() } else { 1.0 };
+                        edge_histogram = None;
+                        edge_color_cache = None;
+                        edge_density_cache = None;
+                        if options.orient_outward {
+                            orient_outward(&mut model);
+                        }
+                        zoom_limits = auto_zoom_limits(&model);
+                        camera_mode = CameraMode::Orbit;
+                        (camera.target, camera.distance) = fit_view(&model);
+                        if flipped_winding {
+                            model.flip_winding();
+                        }
+                        morph = None;
+                        session.files.push(path.display().to_string());
+                        println!("Model loaded successfully: {:?}", path);
+                        if model.intensity.is_empty() {
+                            intensity_mode = false;
+                        } else {
+                            println!("Model has per-vertex intensity, press I to display it");
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("Error loading model: {}", err);
+                    }
+                }
+            } else {
+                println!("No file was selected");
+            }
+        }
+
+        //////////////////////////////////////////////////////////////////////////////////////
+        // Press F9 to export the wireframe as SVG, or Shift + F9 to save a PNG
+        // screenshot (both written once this frame is drawn)
+        //////////////////////////////////////////////////////////////////////////////////////
+        let mut svg_path = None;
+        let mut screenshot_path = None;
+        if window.is_key_pressed(Key::F9, minifb::KeyRepeat::No) && shift {
+            screenshot_path = FileDialog::new()
+                .add_filter("PNG image", &["png"])
+                .show_save_single_file()
+                .unwrap();
+            if screenshot_path.is_none() {
+                println!("No file was selected");
+            }
+        } else if window.is_key_pressed(Key::F9, minifb::KeyRepeat::No) {
+            svg_path = FileDialog::new()
+                .add_filter("SVG image", &["svg"])
+                .show_save_single_file()
+                .unwrap();
+            if svg_path.is_none() {
+                println!("No file was selected");
+            }
+        }
+        let mut svg_segments: Vec<export::Segment> = Vec::new();
+
+        // Press Shift+F10 to save the model as OBJ, with Ctrl as currently rotated
+        if shift && window.is_key_pressed(Key::F10, minifb::KeyRepeat::No) {
+            if let Some(path) = FileDialog::new()
+                .add_filter("Wavefront OBJ", &["obj"])
+                .show_save_single_file()
+                .unwrap()
+            {
+                let result = if ctrl {
+                    let view = camera.rotation();
+                    let mut rotated = model.clone();
+                    for v in &mut rotated.vertices {
+                        *v = view.apply(*v);
+                    }
+                    export::save_obj(&rotated, path.to_str().unwrap())
+                } else {
+                    export::save_obj(&model, path.to_str().unwrap())
+                };
+                match result {
+                    Ok(()) => println!("Model saved to {:?} ({} vertices, {} faces)", path, model.vertices.len(), model.faces.len()),
+                    Err(err) => eprintln!("Error saving OBJ: {}", err),
+                }
+            } else {
+                println!("No file was selected");
+            }
+        }
+
+        // Press F10 to export the model's edges as DXF
+        if !shift && window.is_key_pressed(Key::F10, minifb::KeyRepeat::No) {
+            if let Some(path) = FileDialog::new()
+                .add_filter("DXF drawing", &["dxf"])
+                .show_save_single_file()
+                .unwrap()
+            {
+                match export::write_dxf(path.to_str().unwrap(), &model) {
+                    Ok(()) => println!("Model exported to {:?} ({} edges)", path, model.edges.len()),
+                    Err(err) => eprintln!("Error exporting DXF: {}", err),
+                }
+            } else {
+                println!("No file was selected");
+            }
+        }
+
+        //////////////////////////////////////////////////////////////////////////////////////
+        // Drawing the 3D model
+        //////////////////////////////////////////////////////////////////////////////////////
+        // We rotate each vertex around X, Y, Z, then project it using a simple perspective:
+        //
+        // $$ x' = x \cos(\theta_x) + \dots $$
+        // $$ u = x' \frac{\text{scale}}{z' + \text{distance}} + \frac{\text{width}}{2} $$
+        // $$ v = -y' \frac{\text{scale}}{z' + \text{distance}} + \frac{\text{height}}{2} $$
+        
+        if show_hull && hull.as_ref().is_none_or(|(vertices, _)| *vertices != model.vertices) {
+            let cage = model.convex_hull();
+            println!("Convex hull: {} vertices, {} faces", cage.vertices.len(), cage.faces.len());
+            hull = Some((model.vertices.clone(), cage));
+        }
+
+        // Grid snapping only changes what is drawn, so draw a snapped copy
+        let snapped_model;
+        let model = if snap_to_grid {
+            let mut snapped = model.clone();
+            snapped.snap_to_grid(grid_step);
+            snapped_model = snapped;
+            &snapped_model
+        } else {
+            &model
+        };
+
+        // Exploded facets are drawn from a copy as well
+        let exploded_model;
+        let model = if explode > 0.0 {
+            exploded_model = model.exploded(explode * model.diagonal());
+            &exploded_model
+        } else {
+            model
+        };
+
+        // Text lines shown in the top-left corner this frame
+        let mut overlay: Vec<String> = Vec::new();
+        if show_info {
+            overlay.extend(model_info(model));
+        }
+        if show_measurements {
+            let (min, max) = model.bounding_box();
+            let units = options.units.as_deref().unwrap_or("units");
+            overlay.push(format!(
+                "Size: W {:.3} x H {:.3} x D {:.3} {}",
+                max.0 - min.0,
+                max.1 - min.1,
+                max.2 - min.2,
+                units
+            ));
+        }
+
+        let current_flags = ModeFlags {
+            silhouette,
+            filled,
+            shaded,
+            area: area_mode,
+            depth: depth_mode,
+            normals: show_normals,
+        };
+
+        // The whole window, the two stereo halves, or a 2x2 grid of panels that
+        // each force one render mode
+        let (half_width, half_height) = (width / 2, height / 2);
+        let viewports: Vec<(usize, usize, usize, usize, Option<RenderMode>)> = if stereo {
+            vec![(0, 0, half_width, height, None), (half_width, 0, width - half_width, height, None)]
+        } else if quad_layout {
+            vec![
+                (0, 0, half_width, half_height, Some(RenderMode::Wireframe)),
+                (half_width, 0, width - half_width, half_height, Some(RenderMode::Shaded)),
+                (0, half_height, half_width, height - half_height, Some(RenderMode::Depth)),
+                (half_width, half_height, width - half_width, height - half_height, Some(RenderMode::Normals)),
+            ]
+        } else {
+            vec![(0, 0, width, height, None)]
+        };
+
+        // A stretched, resized window scales the buffer unevenly; squeeze x by
+        // the same ratio so the model keeps its proportions on screen
+        let pixel_aspect = if matches!(options.scale_mode, Some(minifb::ScaleMode::Stretch)) {
+            let (window_width, window_height) = window.get_size();
+            (window_width as f32 / width as f32) / (window_height.max(1) as f32 / height as f32)
+        } else {
+            1.0
+        };
+
+        // The camera is fixed for the frame, so its rotation is built once
+        let rotation = camera.rotation();
+        let distance = camera.distance;
+        // Every vertex is moved to the target and rotated once per frame; edges
+        // and faces look them up
+        let to_view = |p: (f32, f32, f32)| rotation.apply((Vec3::from(p) - camera.target).into());
+        let rotated: Vec<(f32, f32, f32)> = model.vertices.iter().map(|&p| to_view(p)).collect();
+
+        // Mouse position for the hovered-vertex label
+        let hover_mouse = if show_hover { window.get_mouse_pos(MouseMode::Discard) } else { None };
+
+        // Parts of a multi-object model each get their own color in place of the
+        // object color
+        let part_colors = object_colors(&colors, obj_color, bg_color, model.objects.len());
+        let (edge_parts, face_parts) = (model.edge_objects(), model.face_objects());
+        let part_color = |parts: &[Option<usize>], i: usize| parts.get(i).copied().flatten().map_or(colors[obj_color], |k| part_colors[k]);
+
+        let (frame, frame_width) = (&mut buffer, width);
+        for (index, &(left, top, width, height, panel)) in viewports.iter().enumerate() {
+            // Tagged so screenshots can tell the background from drawn pixels
+            let mut buffer = vec![(colors[bg_color] & 0xFFFFFF) | rendering::BACKGROUND_TAG; width * height];
+            if let (Some(image), true) = (&reference, show_reference) {
+                image.draw(&mut buffer, width, height, reference_scale, reference_blend);
+            }
+            let (silhouette, filled, shaded, area_mode, depth_mode, show_normals) = match panel.map(RenderMode::flags) {
+                None => (silhouette, filled, shaded, area_mode, depth_mode, show_normals),
+                Some(f) => (f.silhouette, f.filled, f.shaded, f.area, f.depth, f.normals),
+            };
+            // Only the first viewport is exported to SVG
+            let svg_path = svg_path.as_ref().filter(|_| index == 0);
+            // Panels are half the window height, which halves the scale to match
+            let scale = camera.scale(height);
+
+            // Stereo eyes sit left and right of the camera, looking parallel; the
+            // images are shifted towards each other so that points at the
+            // convergence distance line up
+            let eye_x = match (stereo, index) {
+                (false, _) => 0.0,
+                (true, 0) => -0.5 * eye_separation * distance,
+                (true, _) => 0.5 * eye_separation * distance,
+            };
+            let eye_rotated;
+            let rotated = if eye_x != 0.0 {
+                eye_rotated = rotated.iter().map(|&(x, y, z)| (x - eye_x, y, z)).collect::<Vec<_>>();
+                &eye_rotated
+            } else {
+                &rotated
+            };
+            let to_view = |p: (f32, f32, f32)| {
+                let (x, y, z) = to_view(p);
+                (x - eye_x, y, z)
+            };
+
+            // Lens shift is kept as a fraction of the height, so panels shift alike
+            let stereo_shift = eye_x * scale / (convergence * distance) / pixel_aspect;
+            let screen = transformations::Screen {
+                shift: (lens_shift.0 * height as f32 + stereo_shift, lens_shift.1 * height as f32),
+                near,
+                pixel_aspect,
+                // Orthographic views keep the size the target has in perspective
+                orthographic: orthographic.then_some(distance),
+                ..transformations::Screen::new(width, height)
+            };
+
+            // Rotates a model-space point and projects it to the screen
+            let project = |p: (f32, f32, f32)| {
+                let (rx, ry, rz) = to_view(p);
+                transformations::project_perspective(rx, ry, rz, distance, scale, screen)
+            };
+            let project_subpixel = |p: (f32, f32, f32)| {
+                let (rx, ry, rz) = to_view(p);
+                transformations::project_subpixel(rx, ry, rz, distance, scale, screen)
+            };
+            // Rotates a model-space segment and projects it like the wireframe edges:
+            // trimmed to the near plane and then to the screen
+            let project_segment = |from: (f32, f32, f32), to: (f32, f32, f32)| {
+                let to_camera = |p: (f32, f32, f32)| {
+                    let (x, y, z) = to_view(p);
+                    (x, y, z + distance)
+                };
+                let (c1, c2) = transformations::clip_line(to_camera(from), to_camera(to), near)?;
+                let (a, b) = (transformations::project_camera(c1, scale, screen), transformations::project_camera(c2, scale, screen));
+                let (a, b) = transformations::clip_to_screen(a, b, screen)?;
+                Some(((a.0 as usize, a.1 as usize), (b.0 as usize, b.1 as usize)))
+            };
+            let inside_clip_box = |p: (f32, f32, f32)| clip_box.as_ref().is_none_or(|aabb| aabb.contains(p));
+
+            // Intensity range used to normalize the per-vertex scalar to [0, 1]
+            let (min_intensity, max_intensity) = model
+                .intensity
+                .iter()
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+            let intensity_span = (max_intensity - min_intensity).max(f32::EPSILON);
+
+            //////////////////////////////////////////////////////////////////////////////////////
+            // Ground grid: a faint floor on the XZ plane at the model's lowest point,
+            // drawn first so the model covers it
+            //////////////////////////////////////////////////////////////////////////////////////
+            if show_ground {
+                let (min, _) = model.bounds();
+                let spacing = nice_length(bounding_radius(model).max(f32::EPSILON));
+                let color = rendering::blend_color(colors[bg_color], rendering::contrast_color(colors[bg_color]), GROUND_GRID_ALPHA, false);
+                let to_camera = |p: (f32, f32, f32)| {
+                    let (x, y, z) = to_view(p);
+                    (x, y, z + distance)
+                };
+                for (from, to) in ground_grid(GROUND_GRID_LINES, spacing, min.y) {
+                    // Trim to the near plane and the screen, as the floor often reaches behind the camera
+                    let Some((c1, c2)) = transformations::clip_line(to_camera(from), to_camera(to), near) else {
+                        continue;
+                    };
+                    let (a, b) = (transformations::project_camera(c1, scale, screen), transformations::project_camera(c2, scale, screen));
+                    if let Some((a, b)) = transformations::clip_to_screen(a, b, screen) {
+                        rendering::draw_line(&mut buffer, width, height, (a.0 as usize, a.1 as usize), (b.0 as usize, b.1 as usize), color);
+                    }
+                }
+            }
+
+            // Screen position of every vertex in front of the near plane, projected
+            // once per viewport and shared by the silhouette and by all the edges
+            // meeting there
+            let projected_vertices: Vec<Option<(f32, f32)>> = rotated
+                .iter()
+                .map(|&(x, y, z)| {
+                    (z + distance >= near).then(|| transformations::project_camera((x, y, z + distance), scale, screen))
+                })
+                .collect();
+
+            if silhouette {
+                // Every face is filled regardless of winding, so the union covers the
+                // whole silhouette even on meshes with inconsistent orientation.
+                // Triangles with a corner off the screen are skipped.
+                let on_screen = |&(u, v): &(f32, f32)| u >= 0.0 && u < width as f32 && v >= 0.0 && v < height as f32;
+                let projected: Vec<Option<(usize, usize)>> = projected_vertices
+                    .iter()
+                    .map(|p| p.filter(on_screen).map(|(u, v)| (u as usize, v as usize)))
+                    .collect();
+
+                for (face, &center) in model.faces.iter().zip(&model.face_centers()) {
+                    if !inside_clip_box(center) {
+                        continue;
+                    }
+
+                    // Fan triangulation: (v0, vi, vi+1)
+                    for i in 1..face.len().saturating_sub(1) {
+                        if let (Some(p0), Some(p1), Some(p2)) =
+                            (projected[face[0]], projected[face[i]], projected[face[i + 1]])
+                        {
+                            rendering::fill_triangle(&mut buffer, width, height, p0, p1, p2, colors[obj_color]);
+                        }
+                    }
+                }
+            }
+
+            // Face-area coloring: degenerate faces in red, the rest in grays that
+            // grow lighter with the face's area relative to the median face
+            let area_colors: Option<Vec<u32>> = (area_mode && !silhouette).then(|| {
+                let areas = model.face_areas();
+                let threshold = DEGENERATE_AREA_RATIO * model.diagonal().powi(2);
+
+                let mut sorted = areas.clone();
+                sorted.sort_by(f32::total_cmp);
+                let median = sorted.get(sorted.len() / 2).copied().unwrap_or(0.0).max(f32::EPSILON);
+
+                let degenerate = areas.iter().filter(|&&a| a <= threshold).count();
+                overlay.push(format!("Faces: {} | near-zero area: {}", areas.len(), degenerate));
+
+                areas
+                    .iter()
+                    .map(|&a| {
+                        if a <= threshold {
+                            RED
+                        } else {
+                            colormap::map(0.3 + 0.3 * (a / median).min(2.0), colormap::Scheme::Grayscale)
+                        }
+                    })
+                    .collect()
+            });
+
+            if (filled || shaded || depth_mode || area_colors.is_some()) && !silhouette {
+                let (min_depth, max_depth) = rotated
+                    .iter()
+                    .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &(_, _, z)| (lo.min(z), hi.max(z)));
+                let depth_span = (max_depth - min_depth).max(f32::EPSILON);
+                let projected: Vec<Option<(usize, usize, f32)>> = rotated
+                    .iter()
+                    .map(|&(x, y, z)| transformations::project_depth(x, y, z, distance, scale, screen))
+                    .collect();
+
+                // The depth buffer keeps the nearest face at every pixel, whatever
+                // order the faces are drawn in
+                let mut depth_buffer = rendering::DepthBuffer::new(width, height);
+                let centers = model.face_centers();
+                for (f, ((face, &(nx, ny, nz)), &(cx, cy, cz))) in
+                    model.faces.iter().zip(&model.face_normals()).zip(&centers).enumerate()
+                {
+                    if !inside_clip_box((cx, cy, cz)) {
+                        continue;
+                    }
+                    let normal = rotation.apply((nx, ny, nz));
+
+                    // View vector: from the face center towards the camera at (0, 0, -distance)
+                    let (cx, cy, cz) = to_view((cx, cy, cz));
+                    // (orthographic views look straight down the -z axis everywhere)
+                    let view = if orthographic {
+                        Vec3::new(0.0, 0.0, -1.0)
+                    } else {
+                        Vec3::new(-cx, -cy, -distance - cz).normalize()
+                    };
+                    if cull_back_faces && !front_face.is_front(Vec3::from(normal).dot(view)) {
+                        continue;
+                    }
+
+                    let color = match &area_colors {
+                        Some(colors) => colors[f],
+                        // Nearest faces at the top of the colormap
+                        None if depth_mode => colormap::map(1.0 - (cz - min_depth) / depth_span, colormap_scheme),
+                        None => {
+                            let base = model.face_colors[f].unwrap_or_else(|| part_color(&face_parts, f));
+                            if shaded {
+                                rendering::shade_color_rgb(base, lighting.shade(normal, view.into(), &lights))
+                            } else {
+                                base
+                            }
+                        }
+                    };
+
+                    for i in 1..face.len().saturating_sub(1) {
+                        let (a, b, c) = (face[0], face[i], face[i + 1]);
+                        if let (Some(p0), Some(p1), Some(p2)) = (projected[a], projected[b], projected[c]) {
+                            depth_buffer.fill_triangle(&mut buffer, p0, p1, p2, color);
+                        }
+                    }
+                }
+            }
+
+            // The wireframe is hidden by filled modes, but still projected for SVG export
+            let draw_wireframe = !silhouette && !filled && !shaded && !area_mode && !depth_mode;
+            let mut stats = RenderStats::default();
+            let mut edge_count: usize = 0;
+            if edge_color_cache.as_ref().is_none_or(|&(exploded, _)| exploded != (explode > 0.0)) {
+                edge_color_cache = Some((explode > 0.0, model.edge_colors()));
+            }
+            let edge_colors = edge_color_cache.as_ref().map_or(&[][..], |(_, colors)| colors);
+            let mut coverage = (msaa && draw_wireframe).then(|| rendering::CoverageBuffer::new(width, height, msaa_pattern));
+
+            // Edge crowding, for the occlusion-like darkening
+            if edge_occlusion
+                && draw_wireframe
+                && edge_density_cache.as_ref().is_none_or(|(vertices, _)| *vertices != model.vertices)
+            {
+                let density = model.edge_density(model.diagonal() * OCCLUSION_RADIUS);
+                edge_density_cache = Some((model.vertices.clone(), density));
+            }
+            let edge_density = edge_density_cache.as_ref().filter(|_| edge_occlusion && draw_wireframe).map(|(_, density)| density);
+
+            // Depth range of the rotated model, for depth-based alpha
+            let depth_range = (depth_alpha && draw_wireframe).then(|| {
+                rotated.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &(_, _, z)| (lo.min(z), hi.max(z)))
+            });
+
+            // Camera-space depth window of the depth slab, from the rotated model's depth range
+            let slab_bounds = depth_slab.map(|(center, thickness)| {
+                let (min_depth, max_depth) =
+                    rotated.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &(_, _, z)| (lo.min(z), hi.max(z)));
+                let span = max_depth - min_depth;
+                (min_depth + (center - thickness / 2.0) * span, min_depth + (center + thickness / 2.0) * span)
+            });
+            if let (Some((lo, hi)), 0) = (slab_bounds, index) {
+                overlay.push(format!("Depth slab: {:.3} .. {:.3}", lo + distance, hi + distance));
+            }
+
+            for (e, (&(i1, i2), &flags)) in model.edges.iter().zip(&model.edge_flags).enumerate() {
+                if !edge_source.includes(flags) || (!show_diagonals && model::is_diagonal_only(flags)) {
+                    continue;
+                }
+                // Count only the edges passing the filters, so the stride stays even
+                edge_count += 1;
+                if !(edge_count - 1).is_multiple_of(edge_stride) {
+                    continue;
+                }
+                if !draw_wireframe && svg_path.is_none() {
+                    continue;
+                }
+
+                let (mut r1, mut r2) = (rotated[i1], rotated[i2]);
+
+                // Skip edges whose mean depth lies outside the depth slab
+                if let Some((lo, hi)) = slab_bounds {
+                    if !(lo..=hi).contains(&((r1.2 + r2.2) / 2.0)) {
+                        stats.clipped += 1;
+                        continue;
+                    }
+                }
+
+                // Trim the edge to the clipping box, in model space
+                if let Some(aabb) = &clip_box {
+                    match aabb.clip_segment(model.vertices[i1], model.vertices[i2]) {
+                        Some((c1, c2)) => (r1, r2) = (to_view(c1), to_view(c2)),
+                        None => {
+                            stats.clipped += 1;
+                            continue;
+                        }
+                    }
+                }
+
+                // Look up the projected endpoints, or for edges trimmed by the clipping
+                // box or crossing the near plane trim them in camera space and project
+                // the new ends. Then trim the edge to the screen.
+                let (a, b) = match (clip_box.is_none(), projected_vertices[i1], projected_vertices[i2]) {
+                    (true, Some(a), Some(b)) => (a, b),
+                    _ => {
+                        let to_camera = |(x, y, z): (f32, f32, f32)| (x, y, z + distance);
+                        let Some((c1, c2)) = transformations::clip_line(to_camera(r1), to_camera(r2), near) else {
+                            stats.culled += 1;
+                            continue;
+                        };
+                        (
+                            transformations::project_camera(c1, scale, screen),
+                            transformations::project_camera(c2, scale, screen),
+                        )
+                    }
+                };
+                let Some((a, b)) = transformations::clip_to_screen(a, b, screen) else {
+                    stats.offscreen += 1;
+                    continue;
+                };
+                let (start, end) = ((a.0 as usize, a.1 as usize), (b.0 as usize, b.1 as usize));
+
+                let color = if intensity_mode {
+                    let mean = (model.intensity[i1] + model.intensity[i2]) / 2.0;
+                    colormap::map((mean - min_intensity) / intensity_span, colormap_scheme)
+                } else {
+                    edge_colors.get(e).copied().flatten().unwrap_or_else(|| part_color(&edge_parts, e))
+                };
+
+                let shade = |color: u32| match &edge_density {
+                    Some(density) => rendering::shade_color(color, 1.0 - OCCLUSION_STRENGTH * density[e]),
+                    None => color,
+                };
+                let color = shade(color);
+                // Edges between two colored vertices fade from one color to the other
+                // (the other line styles use the mean color from `edge_colors`)
+                let end_colors = match (intensity_mode, model.vertex_colors.get(i1), model.vertex_colors.get(i2)) {
+                    (false, Some(&Some(c1)), Some(&Some(c2))) => Some((shade(c1), shade(c2))),
+                    _ => None,
+                };
+
+                if svg_path.is_some() {
+                    svg_segments.push((start, end, color));
+                }
+
+                if !draw_wireframe {
+                    continue;
+                }
+
+                // Fade edges whose mean depth lies in the far half of the model
+                let mut blend = blend;
+                if let Some((min_depth, max_depth)) = depth_range {
+                    let t = ((r1.2 + r2.2) / 2.0 - min_depth) / (max_depth - min_depth).max(f32::EPSILON);
+                    let far = ((t - 0.5) * 2.0).clamp(0.0, 1.0);
+                    blend.alpha *= near_alpha + (far_alpha - near_alpha) * far;
+                }
+
+                if stippled {
+                    rendering::draw_stippled_line(&mut buffer, width, height, start, end, color, stipple);
+                } else if let Some(coverage) = coverage.as_mut() {
+                    coverage.draw_line(a, b, color);
+                } else if blend.alpha < 1.0 {
+                    rendering::draw_line_alpha(&mut buffer, width, height, start, end, color, blend);
+                } else if smooth_lines {
+                    rendering::draw_line_aa(&mut buffer, width, height, a, b, color, blend.linear);
+                } else if subpixel_lines && line_width == 1 {
+                    rendering::draw_line_subpixel(&mut buffer, width, height, a, b, color);
+                } else if line_width > 1 {
+                    rendering::draw_line_thick(&mut buffer, width, height, start, end, color, line_width);
+                } else if let Some((start_color, end_color)) = end_colors {
+                    rendering::draw_line_gradient(&mut buffer, width, height, start, end, start_color, end_color);
+                } else {
+                    rendering::draw_line(&mut buffer, width, height, start, end, color);
+                }
+                stats.drawn += 1;
+            }
+            if let Some(coverage) = &coverage {
+                coverage.resolve(&mut buffer, blend.linear);
+            }
+            frame_edges += stats.drawn;
+
+            if edge_stride > 1 {
+                overlay.push(format!("Edge stride: every {}", edge_stride));
+            }
+            if show_stats && index == 0 {
+                overlay.push(format!(
+                    "Edges: drawn {} | clipped {} | culled {} | off-screen {}",
+                    stats.drawn, stats.clipped, stats.culled, stats.offscreen
+                ));
+            }
+
+            if let Some(path) = &svg_path {
+                match export::write_svg(path.to_str().unwrap(), width, height, colors[bg_color], &svg_segments) {
+                    Ok(()) => println!("Wireframe exported to {:?} ({} edges)", path, svg_segments.len()),
+                    Err(err) => eprintln!("Error exporting SVG: {}", err),
+                }
+            }
+
+            //////////////////////////////////////////////////////////////////////////////////////
+            // Convex hull cage around the model
+            //////////////////////////////////////////////////////////////////////////////////////
+            if let (true, Some((_, cage))) = (show_hull, &hull) {
+                for &(a, b) in &cage.edges {
+                    if let Some((start, end)) = project_segment(cage.vertices[a], cage.vertices[b]) {
+                        rendering::draw_line(&mut buffer, width, height, start, end, HULL_COLOR);
+                    }
+                }
+            }
+
+            //////////////////////////////////////////////////////////////////////////////////////
+            // World axes: X red, Y green, Z blue
+            //////////////////////////////////////////////////////////////////////////////////////
+            if show_axes {
+                let radius = bounding_radius(model);
+                for (from, to, color) in axis_lines(if radius > 0.0 { radius } else { 1.0 }) {
+                    if let Some((start, end)) = project_segment(from, to) {
+                        rendering::draw_line(&mut buffer, width, height, start, end, color);
+                    }
+                }
+            }
+
+            //////////////////////////////////////////////////////////////////////////////////////
+            // Clipping box outline, with the selected face highlighted
+            //////////////////////////////////////////////////////////////////////////////////////
+            if let Some(aabb) = &clip_box {
+                let corners = aabb.corners();
+                let (axis_bit, side) = (1 << (clip_face / 2), clip_face % 2);
+                for a in 0..8 {
+                    for bit in [1, 2, 4] {
+                        let b = a | bit;
+                        if b == a {
+                            continue;
+                        }
+                        // An edge lies on the selected face when both corners do
+                        let on_face = |i: usize| (i & axis_bit != 0) == (side == 1);
+                        let color = if on_face(a) && on_face(b) { NORMAL_COLOR } else { CLIP_BOX_COLOR };
+                        if let Some((start, end)) = project_segment(corners[a], corners[b]) {
+                            rendering::draw_line(&mut buffer, width, height, start, end, color);
+                        }
+                    }
+                }
+            }
+
+            //////////////////////////////////////////////////////////////////////////////////////
+            // Normals hedgehog
+            //////////////////////////////////////////////////////////////////////////////////////
+            if show_normals {
+                let (origins, normals) = if use_vertex_normals {
+                    (model.vertices.clone(), model.vertex_normals())
+                } else {
+                    (model.face_centers(), model.face_normals())
+                };
+
+                for (&(x, y, z), &(nx, ny, nz)) in origins.iter().zip(&normals).step_by(normal_stride) {
+                    if !inside_clip_box((x, y, z)) {
+                        continue;
+                    }
+                    let tip = (x + nx * normal_length, y + ny * normal_length, z + nz * normal_length);
+
+                    if let (Some(start), Some(end)) = (project((x, y, z)), project(tip)) {
+                        rendering::draw_line(&mut buffer, width, height, start, end, NORMAL_COLOR);
+                    }
+                }
+
+                overlay.push(format!(
+                    "Normals: {} | every {} | length {:.2}",
+                    if use_vertex_normals { "vertex" } else { "face" },
+                    normal_stride,
+                    normal_length
+                ));
+            }
+
+            //////////////////////////////////////////////////////////////////////////////////////
+            // Hovered vertex: its model-space and projected pixel coordinates
+            //////////////////////////////////////////////////////////////////////////////////////
+            if let Some((mx, my)) = hover_mouse {
+                let (mx, my) = (mx - left as f32, my - top as f32);
+                let nearest = model
+                    .vertices
+                    .iter()
+                    .filter(|&&p| inside_clip_box(p))
+                    .filter_map(|&p| project_subpixel(p).map(|uv| (p, uv)))
+                    .map(|(p, (u, v))| (p, (u, v), (u - mx).powi(2) + (v - my).powi(2)))
+                    .filter(|&(_, _, d2)| d2 <= HOVER_RADIUS * HOVER_RADIUS)
+                    .min_by(|a, b| a.2.total_cmp(&b.2));
+
+                if let Some(((x, y, z), (u, v), _)) = nearest {
+                    let color = rendering::contrast_color(colors[bg_color]);
+                    let (px, py) = (u as usize, v as usize);
+                    rendering::fill_rect(&mut buffer, width, height, (px as isize - 2, py as isize - 2, 5, 5), color);
+
+                    let lines = [format!("({:.3}, {:.3}, {:.3})", x, y, z), format!("px ({:.1}, {:.1})", u, v)];
+                    let longest = lines.iter().map(String::len).max().unwrap_or(0) * 8;
+                    // Keep the label inside the viewport, flipping it left/up near the edges
+                    let lx = if px + 8 + longest < width { px + 8 } else { px.saturating_sub(8 + longest) };
+                    let ly = if py >= 2 * TEXT_LINE_HEIGHT + 4 { py - 2 * TEXT_LINE_HEIGHT - 4 } else { py + 8 };
+                    for (i, line) in lines.iter().enumerate() {
+                        rendering::draw_text(&mut buffer, width, height, (lx, ly + i * TEXT_LINE_HEIGHT), line, color);
+                    }
+                }
+            }
+
+            // Vertex indices (0-based, in file order) next to each on-screen vertex
+            if show_indices && model.vertices.len() <= MAX_INDEX_LABELS {
+                let color = rendering::contrast_color(colors[bg_color]);
+                for (i, (&p, &uv)) in model.vertices.iter().zip(&projected_vertices).enumerate() {
+                    let Some((u, v)) = uv.filter(|&(u, v)| u >= 0.0 && v >= 0.0 && inside_clip_box(p)) else {
+                        continue;
+                    };
+                    rendering::draw_text(&mut buffer, width, height, (u as usize + 3, v as usize + 3), &i.to_string(), color);
+                }
+            }
+
+            if let Some(panel) = panel {
+                let label = panel.name();
+                let x = width.saturating_sub(label.len() * 8) / 2;
+                rendering::draw_text(&mut buffer, width, height, (x, 4), label, rendering::contrast_color(colors[bg_color]));
+            }
+
+            // Copy the viewport into its place in the window
+            for row in 0..height {
+                let start = (top + row) * frame_width + left;
+                frame[start..start + width].copy_from_slice(&buffer[row * width..(row + 1) * width]);
+            }
+        }
+
+        // Panels can report the same lines, keep each once
+        let mut unique: Vec<String> = Vec::new();
+        for line in overlay {
+            if !unique.contains(&line) {
+                unique.push(line);
+            }
+        }
+        let overlay = unique;
+
+        // Panel dividers
+        if quad_layout && !stereo {
+            let color = rendering::contrast_color(colors[bg_color]);
+            rendering::draw_line(&mut buffer, width, height, (half_width, 0), (half_width, height - 1), color);
+            rendering::draw_line(&mut buffer, width, height, (0, half_height), (width - 1, half_height), color);
+        }
+
+        let text_color = rendering::contrast_color(colors[bg_color]);
+        for (i, line) in overlay.iter().enumerate() {
+            rendering::draw_text(&mut buffer, width, height, (8, 8 + i * TEXT_LINE_HEIGHT), line, text_color);
+        }
+
+        //////////////////////////////////////////////////////////////////////////////////////
+        // Scale bar, in the bottom-left corner: model units per pixel at the depth of
+        // the origin, scale / distance pixels per unit
+        //////////////////////////////////////////////////////////////////////////////////////
+        if show_scale_bar {
+            let view_scale = camera.scale(view_height);
+            let pixels_per_unit = view_scale / distance.max(f32::EPSILON) / pixel_aspect;
+            let length = nice_length(SCALE_BAR_MAX_PIXELS / pixels_per_unit);
+            let pixels = (length * pixels_per_unit).round() as usize;
+
+            let (x, y) = (16, height.saturating_sub(40));
+            rendering::draw_line(&mut buffer, width, height, (x, y), (x + pixels, y), text_color);
+            for tick in [x, x + pixels] {
+                rendering::draw_line(&mut buffer, width, height, (tick, y.saturating_sub(4)), (tick, y + 4), text_color);
+            }
+            let units = options.units.as_deref().unwrap_or("units");
+            let label = format!("{} {}", length, units);
+            rendering::draw_text(&mut buffer, width, height, (x, y.saturating_sub(16)), &label, text_color);
+        }
+
+        //////////////////////////////////////////////////////////////////////////////////////
+        // Compass: heading of the view direction around the vertical axis (0 looking
+        // along +Z, 90 along +X) as a needle, with the heading and pitch in degrees
+        //////////////////////////////////////////////////////////////////////////////////////
+        if show_compass {
+            let (_, _, forward) = camera.axes();
+            let heading = forward.x.atan2(forward.z);
+            let pitch = forward.y.clamp(-1.0, 1.0).asin();
+
+            let radius = COMPASS_RADIUS as f32;
+            let (cx, cy) = (width.saturating_sub(COMPASS_RADIUS + 12), COMPASS_RADIUS + 12);
+            let point = |angle: f32, r: f32| {
+                let (sin, cos) = angle.sin_cos();
+                ((cx as f32 + sin * r).round() as usize, (cy as f32 - cos * r).round() as usize)
+            };
+            for i in 0..16 {
+                let step = std::f32::consts::TAU / 16.0;
+                rendering::draw_line(&mut buffer, width, height, point(i as f32 * step, radius), point((i + 1) as f32 * step, radius), text_color);
+            }
+            rendering::draw_text(&mut buffer, width, height, (cx - 3, cy.saturating_sub(COMPASS_RADIUS + 11)), "N", text_color);
+            rendering::draw_line(&mut buffer, width, height, (cx, cy), point(heading, radius - 2.0), RED);
+
+            let lines = [
+                format!("Heading {:.1}", heading.to_degrees().rem_euclid(360.0)),
+                format!("Pitch {:.1}", pitch.to_degrees()),
+            ];
+            for (i, line) in lines.iter().enumerate() {
+                let x = width.saturating_sub(8 + line.len() * 8);
+                rendering::draw_text(&mut buffer, width, height, (x, cy + COMPASS_RADIUS + 8 + i * TEXT_LINE_HEIGHT), line, text_color);
+            }
+        }
+
+        //////////////////////////////////////////////////////////////////////////////////////
+        // Rotation timeline along the bottom: one turn of the Y angle, with ticks
+        // every quarter turn and a marker at the current angle
+        //////////////////////////////////////////////////////////////////////////////////////
+        if show_timeline {
+            let (x, y) = (TIMELINE_MARGIN.0, timeline_y as usize);
+            let track = timeline_width as usize;
+            rendering::fill_rect(&mut buffer, width, height, (x as isize, y as isize - 1, track, 3), text_color);
+            for quarter in 0..=4 {
+                let tick = x + track * quarter / 4;
+                rendering::draw_line(&mut buffer, width, height, (tick, y.saturating_sub(4)), (tick, y + 4), text_color);
+            }
+
+            let angle = camera.yaw.rem_euclid(std::f32::consts::TAU);
+            let marker = x + (angle / std::f32::consts::TAU * track as f32) as usize;
+            rendering::fill_rect(&mut buffer, width, height, (marker as isize - 1, y as isize - 6, 3, 13), RED);
+
+            let label = format!("Y {:.1} deg", angle.to_degrees());
+            let label_x = (x + track).saturating_sub(label.len() * 8);
+            rendering::draw_text(&mut buffer, width, height, (label_x, y.saturating_sub(18)), &label, text_color);
+        }
+
+        //////////////////////////////////////////////////////////////////////////////////////
+        // Edge-length histogram, in the bottom-right corner
+        //////////////////////////////////////////////////////////////////////////////////////
+        if show_histogram {
+            let histogram = edge_histogram.get_or_insert_with(|| Histogram::new(&model.edge_lengths(), HISTOGRAM_BUCKETS));
+            let (w, h) = HISTOGRAM_SIZE;
+            let x = width.saturating_sub(w + 16);
+            let y = height.saturating_sub(h + 40);
+
+            let total: usize = histogram.counts.iter().sum();
+            rendering::draw_text(&mut buffer, width, height, (x, y.saturating_sub(12)), &format!("Edge lengths ({})", total), text_color);
+            rendering::draw_bars(&mut buffer, width, height, (x, y, w, h), &histogram.counts, colors[obj_color]);
+            rendering::draw_line(&mut buffer, width, height, (x, y + h), (x + w, y + h), text_color);
+
+            let max_label = format!("{:.3}", histogram.max);
+            rendering::draw_text(&mut buffer, width, height, (x, y + h + 4), &format!("{:.3}", histogram.min), text_color);
+            rendering::draw_text(&mut buffer, width, height, ((x + w).saturating_sub(max_label.len() * 8), y + h + 4), &max_label, text_color);
+        }
+
+        //////////////////////////////////////////////////////////////////////////////////////
+        // Status bar
+        //////////////////////////////////////////////////////////////////////////////////////
+        if show_status {
+            let mut status: Vec<String> = Vec::new();
+            let mode = RenderMode::matching(current_flags);
+            status.push(mode.map_or("CUSTOM", RenderMode::name).to_uppercase());
+            if stippled {
+                status.push("STIPPLE".to_string());
+            }
+            if line_width > 1 {
+                status.push(format!("{}PX", line_width));
+            }
+            if msaa {
+                status.push(format!("MSAA {}X", msaa_pattern.count()));
+            }
+            if smooth_lines {
+                status.push("SMOOTH".to_string());
+            }
+            if explode > 0.0 {
+                status.push(format!("EXPLODE {:.0}%", explode / MAX_EXPLODE * 100.0));
+            }
+            if cull_back_faces {
+                status.push(format!("CULL {:?}", front_face).to_uppercase());
+            }
+            if edge_occlusion {
+                status.push("OCCLUSION".to_string());
+            }
+            if depth_alpha {
+                status.push(format!("DEPTH ALPHA {:.1}-{:.1}", near_alpha, far_alpha));
+            }
+            if subpixel_lines {
+                status.push("SUBPIXEL".to_string());
+            }
+            if camera_mode == CameraMode::FirstPerson {
+                status.push("FIRST PERSON".to_string());
+            }
+            if stereo {
+                status.push(format!("STEREO {:.3}", eye_separation));
+            }
+            if orthographic {
+                status.push("ORTHO".to_string());
+            }
+            if lens_shift != (0.0, 0.0) {
+                status.push(format!("SHIFT {:+.2} {:+.2}", lens_shift.0, lens_shift.1));
+            }
+            if reference.is_some() && show_reference {
+                status.push(format!("REF {:.0}%", reference_blend.alpha * 100.0));
+            }
+            if flipped_winding {
+                status.push("FLIPPED".to_string());
+            }
+            if snap_to_grid {
+                status.push(format!("GRID 1/{}", GRID_DIVISIONS[grid_division]));
+            }
+            if blend.alpha < 1.0 {
+                status.push(format!("ALPHA {:.1}", blend.alpha));
+            }
+            if blend.linear {
+                status.push("GAMMA".to_string());
+            }
+            if show_diagonals {
+                status.push("DIAGONALS".to_string());
+            }
+            if edge_source != EdgeSource::Combined {
+                status.push(format!("EDGES {:?}", edge_source).to_uppercase());
+            }
+            if intensity_mode {
+                status.push(format!("INTENSITY {:?}", colormap_scheme).to_uppercase());
+            }
+            if shaded {
+                status.push(format!("LIGHTS {}", lights.len()));
+                if light_sweep {
+                    status.push("SWEEP".to_string());
+                }
+                if lighting.specular > 0.0 {
+                    status.push("SPEC".to_string());
+                }
+            }
+            if morph.is_some() {
+                status.push(format!("MORPH {:.0}%", morph_t * 100.0));
+            }
+            if clip_box.is_some() {
+                status.push(format!("CLIP {}", CLIP_FACES[clip_face]));
+            }
+            if let Some((center, thickness)) = depth_slab {
+                status.push(format!("SLAB {:.0}% {:.0}%", center * 100.0, thickness * 100.0));
+            }
+            if show_normals && mode != Some(RenderMode::Normals) {
+                status.push("NORMALS".to_string());
+            }
+            if auto_rotate {
+                status.push(format!("AUTO {:?}", rotate_axes).to_uppercase());
+            }
+            if let Some(time) = flythrough_time {
+                status.push(format!("FLY {:.1}s", time));
+            }
+
+            // Translucent backdrop in the background color keeps the text readable
+            let text_color = rendering::contrast_color(colors[bg_color]);
+            let y = height.saturating_sub(12);
+            let backdrop = rendering::Blend { alpha: 0.75, linear: false };
+            rendering::fill_rect_alpha(&mut buffer, width, height, (0, y as isize - 2, width, 14), colors[bg_color], backdrop);
+            rendering::draw_text(&mut buffer, width, height, (8, y), &status.join(" | "), text_color);
+        }
+
+        if let Some(path) = &screenshot_path {
+            let path = path.to_str().unwrap();
+            let result = if options.transparent {
+                export::write_png_transparent(path, width, height, &buffer)
+            } else {
+                export::write_png(path, width, height, &buffer)
+            };
+            match result {
+                Ok(()) => println!("Screenshot saved to {:?}", path),
+                Err(err) => eprintln!("Error saving screenshot: {}", err),
+            }
+        }
+
+        // Save the flythrough frame and advance the playback clock: by the real
+        // frame time when watching, by a fixed step when exporting frames so the
+        // sequence plays back evenly
+        if let (Some(time), Some(path)) = (flythrough_time, &camera_path) {
+            let step = match &options.frames_dir {
+                Some(dir) => {
+                    let frame_path = std::path::Path::new(dir).join(format!("frame_{:05}.ppm", frame_number));
+                    if let Err(err) = export::write_ppm(&frame_path, width, height, &buffer) {
+                        eprintln!("Error saving frame: {}", err);
+                    }
+                    frame_number += 1;
+                    FRAME_DELAY_MS as f32 / 1000.0
+                }
+                None => frame_time,
+            };
+            let next = time + step;
+            flythrough_time = if next > path.duration() {
+                println!("Flythrough: FINISHED ({} frames saved)", frame_number);
+                None
+            } else {
+                Some(next)
+            };
+        }
+
+        window.update_with_buffer(&buffer, width, height).unwrap();
+        session.frames += 1;
+        session.peak_edges = session.peak_edges.max(frame_edges);
+
+        // Real frame rate and render time in the title, refreshed every second
+        frame_time = frame_timer.tick(frame_start).min(MAX_FRAME_TIME);
+        if frame_timer.title_due() {
+            window.set_title(&format!(
+                "{} | {:.1} fps | {:.1} ms | {} vertices, {} edges",
+                WINDOW_TITLE,
+                frame_timer.fps(),
+                frame_timer.render_time * 1000.0,
+                model.vertices.len(),
+                model.edges.len()
+            ));
+        }
+        // Sleep off what's left of the frame delay, so slow frames aren't held back further
+        thread::sleep(Duration::from_millis(FRAME_DELAY_MS).saturating_sub(frame_start.elapsed()));
+    }
+
+    // Reached both when the window is closed and on Escape
+    session.print_summary();
 }
@@ -0,0 +1,747 @@
+//! In-memory representation of a loaded 3D model and format dispatch.
+
+#[cfg(feature = "zip")]
+use crate::archive;
+use crate::{dxf, obj, ply, stl};
+use m3str3_cube::transformations::{Point3, Vec3};
+use std::{collections::{HashMap, HashSet}, fs, ops::Range, path::Path};
+
+/// Edge lies on the boundary of an `f` face.
+pub const EDGE_FACE: u8 = 1 << 0;
+/// Edge comes from an explicit `l` line element.
+pub const EDGE_LINE: u8 = 1 << 1;
+/// Edge is a diagonal added by fan-triangulating a polygon with more than 3 sides.
+pub const EDGE_DIAGONAL: u8 = 1 << 2;
+
+/// A named part of a model (an OBJ `o` or `g` section), sharing the model's
+/// vertices. Each section's faces and edges are added in file order, so they
+/// are consecutive runs of the model's lists.
+#[derive(Clone, Debug, Default)]
+pub struct SubModel {
+    pub name: String,
+    pub faces: Range<usize>,
+    pub edges: Range<usize>,
+}
+
+/// 3D model structure: stores vertices and edges.
+#[derive(Clone, Default)]
+pub struct Model {
+    pub vertices: Vec<(f32, f32, f32)>,
+    pub edges: Vec<(usize, usize)>,
+    /// Origin of each edge as `EDGE_*` bits, parallel to `edges`.
+    pub edge_flags: Vec<u8>,
+    /// Polygons as lists of vertex indices, in the winding order of the file.
+    pub faces: Vec<Vec<usize>>,
+    /// Optional per-vertex scalar (e.g. PLY `intensity`/`quality`), empty when absent.
+    pub intensity: Vec<f32>,
+    /// Color of each face, parallel to `faces` (`None` uses the object color).
+    pub face_colors: Vec<Option<u32>>,
+    /// Color of each vertex (OBJ `v x y z r g b`), parallel to `vertices`;
+    /// empty when no vertex has one.
+    pub vertex_colors: Vec<Option<u32>>,
+    /// Parts of a file with several objects or groups; empty for a single one.
+    pub objects: Vec<SubModel>,
+}
+
+impl Model {
+    /// The default model: a cube with 8 vertices, 12 edges and 6 outward-facing quads.
+    pub fn cube() -> Model {
+        let mut model = Model {
+            vertices: vec![
+                (-1.0, -1.0, -1.0),
+                ( 1.0, -1.0, -1.0),
+                ( 1.0,  1.0, -1.0),
+                (-1.0,  1.0, -1.0),
+                (-1.0, -1.0,  1.0),
+                ( 1.0, -1.0,  1.0),
+                ( 1.0,  1.0,  1.0),
+                (-1.0,  1.0,  1.0),
+            ],
+            ..Model::default()
+        };
+        model.add_face(vec![0, 3, 2, 1]); // Back (z = -1)
+        model.add_face(vec![4, 5, 6, 7]); // Front (z = +1)
+        model.add_face(vec![0, 4, 7, 3]); // Left
+        model.add_face(vec![1, 2, 6, 5]); // Right
+        model.add_face(vec![0, 1, 5, 4]); // Bottom
+        model.add_face(vec![3, 7, 6, 2]); // Top
+        model
+    }
+
+    /// Adds an edge with the given `EDGE_*` origin. An edge already present
+    /// (in either direction) is not duplicated; it just gains the new flag.
+    pub fn add_edge(&mut self, start: usize, end: usize, flag: u8) {
+        match self.edges.iter().position(|&e| e == (start, end) || e == (end, start)) {
+            Some(i) => self.edge_flags[i] |= flag,
+            None => {
+                self.edges.push((start, end));
+                self.edge_flags.push(flag);
+            }
+        }
+    }
+
+    /// Adds a polygon and its boundary edges (consecutive vertex indices,
+    /// closing back to the first). Polygons are drawn filled as a fan
+    /// `(v0, vi, vi+1)`, so the fan diagonals `(v0, vi)` are added too, flagged
+    /// `EDGE_DIAGONAL` so the wireframe can hide them.
+    pub fn add_face(&mut self, face_indices: Vec<usize>) {
+        for i in 0..face_indices.len() {
+            let start = face_indices[i];
+            let end = face_indices[(i + 1) % face_indices.len()];
+            self.add_edge(start, end, EDGE_FACE);
+        }
+        for i in 2..face_indices.len().saturating_sub(1) {
+            self.add_edge(face_indices[0], face_indices[i], EDGE_DIAGONAL);
+        }
+        self.faces.push(face_indices);
+        self.face_colors.push(None);
+    }
+
+    /// Length of every edge, skipping triangulation diagonals (which aren't
+    /// part of the source geometry).
+    pub fn edge_lengths(&self) -> Vec<f32> {
+        self.edges
+            .iter()
+            .zip(&self.edge_flags)
+            .filter(|&(_, &flags)| !is_diagonal_only(flags))
+            .map(|(&(a, b), _)| {
+                let (p, q) = (self.vertices[a], self.vertices[b]);
+                ((q.0 - p.0).powi(2) + (q.1 - p.1).powi(2) + (q.2 - p.2).powi(2)).sqrt()
+            })
+            .collect()
+    }
+
+    /// How crowded the surroundings of every edge are, in `[0, 1]`: the number
+    /// of other edge midpoints within `radius` of its midpoint, relative to
+    /// the most crowded edge. Parallel to `edges`.
+    ///
+    /// Midpoints are bucketed in a grid of `radius`-sized cells, so only the
+    /// 27 cells around each edge are searched.
+    pub fn edge_density(&self, radius: f32) -> Vec<f32> {
+        let midpoints: Vec<Point3> = self
+            .edges
+            .iter()
+            .map(|&(a, b)| {
+                let (p, q) = (self.vertices[a], self.vertices[b]);
+                ((p.0 + q.0) / 2.0, (p.1 + q.1) / 2.0, (p.2 + q.2) / 2.0)
+            })
+            .collect();
+        let radius = radius.max(f32::EPSILON);
+        let cell = |p: Point3| ((p.0 / radius).floor() as i64, (p.1 / radius).floor() as i64, (p.2 / radius).floor() as i64);
+
+        let mut grid: HashMap<(i64, i64, i64), Vec<usize>> = HashMap::new();
+        for (i, &m) in midpoints.iter().enumerate() {
+            grid.entry(cell(m)).or_default().push(i);
+        }
+
+        let counts: Vec<usize> = midpoints
+            .iter()
+            .enumerate()
+            .map(|(i, &m)| {
+                let (cx, cy, cz) = cell(m);
+                let mut count = 0;
+                for dx in -1..=1 {
+                    for dy in -1..=1 {
+                        for dz in -1..=1 {
+                            let Some(bucket) = grid.get(&(cx + dx, cy + dy, cz + dz)) else { continue };
+                            count += bucket
+                                .iter()
+                                .filter(|&&j| {
+                                    let n = midpoints[j];
+                                    j != i && (n.0 - m.0).powi(2) + (n.1 - m.1).powi(2) + (n.2 - m.2).powi(2) <= radius * radius
+                                })
+                                .count();
+                        }
+                    }
+                }
+                count
+            })
+            .collect();
+
+        let max = counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+        counts.into_iter().map(|c| c as f32 / max).collect()
+    }
+
+    /// Approximate number of bytes held by the model's buffers: the element
+    /// data of every `Vec` plus the per-face `Vec` headers. Normals and
+    /// centers aren't stored (they are computed when drawn), so they don't count.
+    pub fn memory_footprint(&self) -> usize {
+        let face_indices: usize = self.faces.iter().map(|f| f.capacity() * size_of::<usize>()).sum();
+        self.vertices.capacity() * size_of::<Point3>()
+            + self.edges.capacity() * size_of::<(usize, usize)>()
+            + self.edge_flags.capacity() * size_of::<u8>()
+            + self.faces.capacity() * size_of::<Vec<usize>>()
+            + face_indices
+            + self.intensity.capacity() * size_of::<f32>()
+            + self.face_colors.capacity() * size_of::<Option<u32>>()
+            + self.vertex_colors.capacity() * size_of::<Option<u32>>()
+            + self.objects.capacity() * size_of::<SubModel>()
+    }
+
+    /// Moves every vertex by `offset`.
+    pub fn translate(&mut self, offset: Vec3) {
+        for v in &mut self.vertices {
+            *v = (Vec3::from(*v) + offset).into();
+        }
+    }
+
+    /// Moves the model so its bounding-box center lies at the origin, and
+    /// returns the center it was moved from.
+    pub fn center(&mut self) -> Vec3 {
+        let (min, max) = self.bounds();
+        let center = (min + max) * 0.5;
+        self.translate(center * -1.0);
+        center
+    }
+
+    /// Multiplies every vertex coordinate by `factor`, scaling the model
+    /// about the origin.
+    pub fn scale(&mut self, factor: f32) {
+        for v in &mut self.vertices {
+            *v = (Vec3::from(*v) * factor).into();
+        }
+    }
+
+    /// Scales the model so the largest dimension of its bounding box is 1,
+    /// and returns the factor applied (1 for an empty or single-point model).
+    pub fn normalize_scale(&mut self) -> f32 {
+        let (min, max) = self.bounds();
+        let extent = max - min;
+        let largest = extent.x.max(extent.y).max(extent.z);
+        let factor = if largest > 0.0 { 1.0 / largest } else { 1.0 };
+        self.scale(factor);
+        factor
+    }
+
+    /// Rounds every vertex coordinate to the nearest multiple of `step`.
+    pub fn snap_to_grid(&mut self, step: f32) {
+        let snap = |c: f32| (c / step).round() * step;
+        for v in &mut self.vertices {
+            *v = (snap(v.0), snap(v.1), snap(v.2));
+        }
+    }
+
+    /// Copy of the model with every face moved `offset` along its normal,
+    /// each face getting its own copy of its vertices so the surface comes
+    /// apart into floating facets. Edges that don't belong to a face stay on
+    /// the original vertices.
+    pub fn exploded(&self, offset: f32) -> Model {
+        let mut exploded = Model {
+            vertices: self.vertices.clone(),
+            intensity: self.intensity.clone(),
+            vertex_colors: self.vertex_colors.clone(),
+            ..Model::default()
+        };
+        for (&edge, &flags) in self.edges.iter().zip(&self.edge_flags) {
+            if flags & (EDGE_FACE | EDGE_DIAGONAL) == 0 {
+                exploded.edges.push(edge);
+                exploded.edge_flags.push(flags);
+            }
+        }
+
+        // The facets share no vertices, so their edges are pushed directly
+        // instead of searching for duplicates in `add_face`
+        let mut face_edges = Vec::with_capacity(self.faces.len() + 1);
+        for ((face, (nx, ny, nz)), &color) in self.faces.iter().zip(self.face_normals()).zip(&self.face_colors) {
+            face_edges.push(exploded.edges.len());
+            let first = exploded.vertices.len();
+            for &i in face {
+                let (x, y, z) = self.vertices[i];
+                exploded.vertices.push((x + nx * offset, y + ny * offset, z + nz * offset));
+                if let Some(&value) = self.intensity.get(i) {
+                    exploded.intensity.push(value);
+                }
+                if let Some(&color) = self.vertex_colors.get(i) {
+                    exploded.vertex_colors.push(color);
+                }
+            }
+            let n = face.len();
+            for i in 0..n {
+                exploded.edges.push((first + i, first + (i + 1) % n));
+                exploded.edge_flags.push(EDGE_FACE);
+            }
+            for i in 2..n.saturating_sub(1) {
+                exploded.edges.push((first, first + i));
+                exploded.edge_flags.push(EDGE_DIAGONAL);
+            }
+            exploded.faces.push((first..first + n).collect());
+            exploded.face_colors.push(color);
+        }
+
+        // Each part keeps its faces, and with them the edges of its facets
+        face_edges.push(exploded.edges.len());
+        exploded.objects = self
+            .objects
+            .iter()
+            .map(|object| SubModel {
+                name: object.name.clone(),
+                faces: object.faces.clone(),
+                edges: face_edges[object.faces.start]..face_edges[object.faces.end],
+            })
+            .collect();
+        exploded
+    }
+
+    /// Index into `objects` of every edge's part, parallel to `edges`.
+    /// Empty unless the model has several parts.
+    pub fn edge_objects(&self) -> Vec<Option<usize>> {
+        self.part_of(self.edges.len(), |object| object.edges.clone())
+    }
+
+    /// Index into `objects` of every face's part, parallel to `faces`.
+    /// Empty unless the model has several parts.
+    pub fn face_objects(&self) -> Vec<Option<usize>> {
+        self.part_of(self.faces.len(), |object| object.faces.clone())
+    }
+
+    fn part_of(&self, count: usize, range: impl Fn(&SubModel) -> Range<usize>) -> Vec<Option<usize>> {
+        if self.objects.len() < 2 {
+            return Vec::new();
+        }
+        let mut parts = vec![None; count];
+        for (k, object) in self.objects.iter().enumerate() {
+            for part in &mut parts[range(object)] {
+                *part = Some(k);
+            }
+        }
+        parts
+    }
+
+    /// Reverses the winding of every face, turning its normal around.
+    /// The first vertex stays in place, so the fan triangulation (and the
+    /// diagonal edges) remain the same.
+    pub fn flip_winding(&mut self) {
+        for face in &mut self.faces {
+            if face.len() > 1 {
+                face[1..].reverse();
+            }
+        }
+    }
+
+    /// Flips every face whose normal points towards the centroid of the
+    /// vertices, so all normals face outward, and returns how many were
+    /// flipped. A heuristic: it suits closed, roughly convex meshes and can
+    /// misjudge faces in deep concavities.
+    pub fn orient_normals_outward(&mut self) -> usize {
+        let n = self.vertices.len().max(1) as f32;
+        let sum = self.vertices.iter().fold(Vec3::default(), |acc, &v| acc + Vec3::from(v));
+        let centroid = sum * (1.0 / n);
+
+        let inward: Vec<bool> = self
+            .face_normals()
+            .into_iter()
+            .zip(self.face_centers())
+            .map(|(normal, center)| Vec3::from(normal).dot(Vec3::from(center) - centroid) < 0.0)
+            .collect();
+        for (face, _) in self.faces.iter_mut().zip(&inward).filter(|(_, &flip)| flip) {
+            face[1..].reverse();
+        }
+        inward.iter().filter(|&&flip| flip).count()
+    }
+
+    /// The convex hull of the vertices as a triangle mesh with outward-facing
+    /// normals, or an empty model when the vertices are all (nearly) coplanar.
+    ///
+    /// Built incrementally: starting from a tetrahedron of extreme points, every
+    /// point outside the hull so far removes the faces it can see and is joined
+    /// to the horizon, the loop of edges between visible and hidden faces.
+    pub fn convex_hull(&self) -> Model {
+        let points: Vec<Vec3> = self.vertices.iter().map(|&p| Vec3::from(p)).collect();
+        let epsilon = self.diagonal() * 1e-6;
+
+        // Initial tetrahedron: the points farthest apart along X, the point
+        // farthest from their line and the point farthest from their plane
+        let farthest = |score: &dyn Fn(Vec3) -> f32| {
+            (0..points.len()).max_by(|&a, &b| score(points[a]).total_cmp(&score(points[b])))
+        };
+        let (Some(i0), Some(i1)) = (farthest(&|p| -p.x), farthest(&|p| p.x)) else {
+            return Model::default();
+        };
+        let (a, b) = (points[i0], points[i1]);
+        let Some(i2) = farthest(&|p| (b - a).cross(p - a).length()) else {
+            return Model::default();
+        };
+        let normal = (b - a).cross(points[i2] - a);
+        let Some(i3) = farthest(&|p| normal.dot(p - a).abs()) else {
+            return Model::default();
+        };
+        if normal.length() <= epsilon * epsilon || normal.normalize().dot(points[i3] - a).abs() <= epsilon {
+            return Model::default();
+        }
+
+        // Distance of `p` above the plane of a (counterclockwise) triangle
+        let height = |[a, b, c]: [usize; 3], p: Vec3| {
+            let normal = (points[b] - points[a]).cross(points[c] - points[a]).normalize();
+            normal.dot(p - points[a])
+        };
+        let mut faces = vec![[i0, i1, i2], [i0, i3, i1], [i1, i3, i2], [i2, i3, i0]];
+        if height(faces[0], points[i3]) > 0.0 {
+            // The fourth point must lie behind the base triangle
+            for face in &mut faces {
+                face.swap(1, 2);
+            }
+        }
+
+        for (i, &p) in points.iter().enumerate() {
+            let visible: Vec<bool> = faces.iter().map(|&face| height(face, p) > epsilon).collect();
+            if !visible.contains(&true) {
+                continue;
+            }
+            let edges: HashSet<(usize, usize)> = faces
+                .iter()
+                .zip(&visible)
+                .filter(|&(_, &v)| v)
+                .flat_map(|(&[a, b, c], _)| [(a, b), (b, c), (c, a)])
+                .collect();
+            let horizon: Vec<(usize, usize)> = edges.iter().copied().filter(|&(a, b)| !edges.contains(&(b, a))).collect();
+
+            let mut kept: Vec<[usize; 3]> = faces.iter().zip(&visible).filter(|&(_, &v)| !v).map(|(&f, _)| f).collect();
+            kept.extend(horizon.into_iter().map(|(a, b)| [a, b, i]));
+            faces = kept;
+        }
+
+        // Keep only the hull's own vertices, renumbered in order of use
+        let mut index: HashMap<usize, usize> = HashMap::new();
+        let mut hull = Model::default();
+        for face in &faces {
+            let face = face
+                .iter()
+                .map(|&v| {
+                    *index.entry(v).or_insert_with(|| {
+                        hull.vertices.push(self.vertices[v]);
+                        hull.vertices.len() - 1
+                    })
+                })
+                .collect();
+            hull.add_face(face);
+        }
+        hull
+    }
+
+    /// Color of each edge taken from the faces it belongs to (later faces
+    /// win), parallel to `edges`. An edge between two colored vertices takes
+    /// the mean of their colors instead. Empty when nothing has a color.
+    pub fn edge_colors(&self) -> Vec<Option<u32>> {
+        if self.face_colors.iter().all(Option::is_none) && self.vertex_colors.is_empty() {
+            return Vec::new();
+        }
+
+        let index: HashMap<(usize, usize), usize> = self
+            .edges
+            .iter()
+            .enumerate()
+            .map(|(i, &(a, b))| ((a.min(b), a.max(b)), i))
+            .collect();
+
+        let mut colors = vec![None; self.edges.len()];
+        for (face, &color) in self.faces.iter().zip(&self.face_colors) {
+            let Some(color) = color else { continue };
+            let boundary = (0..face.len()).map(|i| (face[i], face[(i + 1) % face.len()]));
+            let diagonals = (2..face.len().saturating_sub(1)).map(|i| (face[0], face[i]));
+            for (a, b) in boundary.chain(diagonals) {
+                if let Some(&e) = index.get(&(a.min(b), a.max(b))) {
+                    colors[e] = Some(color);
+                }
+            }
+        }
+        for (e, &(a, b)) in self.edges.iter().enumerate() {
+            if let (Some(Some(c1)), Some(Some(c2))) = (self.vertex_colors.get(a), self.vertex_colors.get(b)) {
+                colors[e] = Some(mix_colors(*c1, *c2));
+            }
+        }
+        colors
+    }
+
+    /// Checks the model's invariants: every edge and face index in range,
+    /// finite coordinates, faces with at least 3 vertices, and per-edge,
+    /// per-face and per-vertex lists of matching lengths.
+    pub fn validate(&self) -> Result<(), String> {
+        let n = self.vertices.len();
+        if let Some(i) = self.vertices.iter().position(|v| !(v.0.is_finite() && v.1.is_finite() && v.2.is_finite())) {
+            return Err(format!("Vertex {} has a non-finite coordinate", i));
+        }
+        if let Some(&(a, b)) = self.edges.iter().find(|&&(a, b)| a >= n || b >= n) {
+            return Err(format!("Edge ({}, {}) references a missing vertex", a, b));
+        }
+        for (f, face) in self.faces.iter().enumerate() {
+            if face.len() < 3 {
+                return Err(format!("Face {} has only {} vertices", f, face.len()));
+            }
+            if face.iter().any(|&i| i >= n) {
+                return Err(format!("Face {} references a missing vertex", f));
+            }
+        }
+        if self.edge_flags.len() != self.edges.len() || self.face_colors.len() != self.faces.len() {
+            return Err("Edge flags or face colors don't match the edges and faces".to_string());
+        }
+        if !self.intensity.is_empty() && self.intensity.len() != n {
+            return Err(format!("{} intensity values for {} vertices", self.intensity.len(), n));
+        }
+        if let Some(object) = self.objects.iter().find(|o| o.faces.end > self.faces.len() || o.edges.end > self.edges.len()) {
+            return Err(format!("Object '{}' references missing faces or edges", object.name));
+        }
+        if !self.vertex_colors.is_empty() && self.vertex_colors.len() != n {
+            return Err(format!("{} vertex colors for {} vertices", self.vertex_colors.len(), n));
+        }
+        Ok(())
+    }
+
+    /// Minimum and maximum corners of the axis-aligned box enclosing all
+    /// vertices, or two origin points for an empty model.
+    pub fn bounding_box(&self) -> ((f32, f32, f32), (f32, f32, f32)) {
+        if self.vertices.is_empty() {
+            return ((0.0, 0.0, 0.0), (0.0, 0.0, 0.0));
+        }
+        self.vertices.iter().fold(
+            ((f32::INFINITY, f32::INFINITY, f32::INFINITY), (f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY)),
+            |(lo, hi), &(x, y, z)| {
+                ((lo.0.min(x), lo.1.min(y), lo.2.min(z)), (hi.0.max(x), hi.1.max(y), hi.2.max(z)))
+            },
+        )
+    }
+
+    /// Like `bounding_box`, as vectors.
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        let (min, max) = self.bounding_box();
+        (Vec3::from(min), Vec3::from(max))
+    }
+
+    /// Length of the bounding box's diagonal (0 for an empty model).
+    pub fn diagonal(&self) -> f32 {
+        let (min, max) = self.bounds();
+        (max - min).length()
+    }
+
+    /// Unit normal of every face, computed with Newell's method so that
+    /// quads and slightly non-planar polygons still get a stable normal.
+    pub fn face_normals(&self) -> Vec<(f32, f32, f32)> {
+        self.faces.iter().map(|face| Vec3::from(self.newell(face)).normalize().into()).collect()
+    }
+
+    /// Area of every face: half the length of its Newell vector.
+    pub fn face_areas(&self) -> Vec<f32> {
+        self.faces
+            .iter()
+            .map(|face| {
+                let (x, y, z) = self.newell(face);
+                0.5 * (x * x + y * y + z * z).sqrt()
+            })
+            .collect()
+    }
+
+    /// Newell's vector of a polygon: normal to it, with a length of twice its area.
+    ///
+    /// $$ n_x = \sum_i (y_i - y_{i+1})(z_i + z_{i+1}) $$ (and cyclically for \(n_y, n_z\)).
+    fn newell(&self, face: &[usize]) -> (f32, f32, f32) {
+        let mut n = (0.0, 0.0, 0.0);
+        for i in 0..face.len() {
+            let (x0, y0, z0) = self.vertices[face[i]];
+            let (x1, y1, z1) = self.vertices[face[(i + 1) % face.len()]];
+            n.0 += (y0 - y1) * (z0 + z1);
+            n.1 += (z0 - z1) * (x0 + x1);
+            n.2 += (x0 - x1) * (y0 + y1);
+        }
+        n
+    }
+
+    /// Centroid (mean of its vertices) of every face.
+    pub fn face_centers(&self) -> Vec<(f32, f32, f32)> {
+        self.faces
+            .iter()
+            .map(|face| {
+                let sum = face.iter().fold((0.0, 0.0, 0.0), |acc, &i| {
+                    let (x, y, z) = self.vertices[i];
+                    (acc.0 + x, acc.1 + y, acc.2 + z)
+                });
+                let n = face.len().max(1) as f32;
+                (sum.0 / n, sum.1 / n, sum.2 / n)
+            })
+            .collect()
+    }
+
+    /// Per-vertex normal: the normalized sum of the normals of the faces
+    /// sharing the vertex. Vertices that belong to no face get a zero normal.
+    pub fn vertex_normals(&self) -> Vec<(f32, f32, f32)> {
+        let mut normals = vec![Vec3::default(); self.vertices.len()];
+        for (face, n) in self.faces.iter().zip(self.face_normals()) {
+            for &i in face {
+                normals[i] = normals[i] + Vec3::from(n);
+            }
+        }
+        normals.into_iter().map(|n| n.normalize().into()).collect()
+    }
+}
+
+/// Linear blend between the vertex positions of two models with the same
+/// topology (blend shapes / deformation).
+pub struct Morph {
+    pub source: Vec<Point3>,
+    pub target: Vec<Point3>,
+}
+
+impl Morph {
+    /// Creates a morph from `model`'s current vertices to `target`'s, or an error
+    /// when the models don't share the same vertex and edge counts.
+    pub fn new(model: &Model, target: &Model) -> Result<Morph, String> {
+        if model.vertices.len() != target.vertices.len() || model.edges.len() != target.edges.len() {
+            return Err(format!(
+                "Incompatible topology: {} vertices / {} edges vs {} vertices / {} edges",
+                model.vertices.len(),
+                model.edges.len(),
+                target.vertices.len(),
+                target.edges.len()
+            ));
+        }
+        Ok(Morph { source: model.vertices.clone(), target: target.vertices.clone() })
+    }
+
+    /// Writes \( (1 - t) \, s_i + t \, d_i \) for every vertex into `vertices`.
+    pub fn apply(&self, t: f32, vertices: &mut [Point3]) {
+        for ((v, s), d) in vertices.iter_mut().zip(&self.source).zip(&self.target) {
+            *v = (s.0 + (d.0 - s.0) * t, s.1 + (d.1 - s.1) * t, s.2 + (d.2 - s.2) * t);
+        }
+    }
+}
+
+/// Channel-wise mean of two packed `0xRRGGBB` colors.
+fn mix_colors(a: u32, b: u32) -> u32 {
+    let channel = |shift: u32| ((((a >> shift) & 0xFF) + ((b >> shift) & 0xFF)) / 2) << shift;
+    channel(16) | channel(8) | channel(0)
+}
+
+/// Whether an edge with these `EDGE_*` flags exists only as a triangulation
+/// diagonal, i.e. isn't part of the source geometry.
+pub fn is_diagonal_only(flags: u8) -> bool {
+    flags == EDGE_DIAGONAL
+}
+
+/// Which edges make up the wireframe, by origin.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EdgeSource {
+    /// Face boundaries and explicit lines together.
+    Combined,
+    /// Only explicit `l` elements.
+    Lines,
+    /// Only face boundaries.
+    Faces,
+}
+
+impl EdgeSource {
+    /// Returns the next source in the cycle.
+    pub fn next(self) -> EdgeSource {
+        match self {
+            EdgeSource::Combined => EdgeSource::Lines,
+            EdgeSource::Lines => EdgeSource::Faces,
+            EdgeSource::Faces => EdgeSource::Combined,
+        }
+    }
+
+    /// Whether an edge with the given `EDGE_*` flags belongs to this source.
+    pub fn includes(self, flags: u8) -> bool {
+        match self {
+            EdgeSource::Combined => true,
+            EdgeSource::Lines => flags & EDGE_LINE != 0,
+            EdgeSource::Faces => flags & EDGE_FACE != 0,
+        }
+    }
+}
+
+/// Loads a model, choosing the parser from the file extension.
+/// Files with an unknown extension are parsed as `.obj`.
+/// `curve_samples` sets how finely OBJ free-form curves are approximated.
+/// A `.zip` archive loads the first model found inside it (needs the `zip` feature).
+pub fn load_model(file_path: &str, curve_samples: usize) -> Result<Model, String> {
+    if extension_of(file_path).as_deref() == Some("zip") {
+        #[cfg(feature = "zip")]
+        return archive::load_zip(file_path, curve_samples);
+        #[cfg(not(feature = "zip"))]
+        return Err("ZIP archives need the `zip` feature (cargo run --features zip)".to_string());
+    }
+
+    let contents = fs::read(file_path)
+        .map_err(|e| format!("Could not open file: {}", e))?;
+    let dir = Path::new(file_path).parent().unwrap_or(Path::new(""));
+    parse_model(file_path, &contents, curve_samples, &|name| fs::read(dir.join(name)).ok())
+}
+
+/// Parses a model already read into memory, choosing the parser from the
+/// extension of `name` like `load_model` does. Every format but STL (which
+/// may be binary) must be UTF-8 text. Files the model refers to (OBJ
+/// material libraries) are read with `read_file`, which takes the name used
+/// in the model and returns `None` if there's no such file.
+pub fn parse_model(
+    name: &str,
+    contents: &[u8],
+    curve_samples: usize,
+    read_file: &dyn Fn(&str) -> Option<Vec<u8>>,
+) -> Result<Model, String> {
+    let extension = extension_of(name);
+    if extension.as_deref() == Some("stl") {
+        return stl::parse_stl(contents);
+    }
+
+    let contents = std::str::from_utf8(contents)
+        .map_err(|_| "Could not read file: not valid UTF-8 text".to_string())?;
+    match extension.as_deref() {
+        Some("ply") => ply::parse_ply(contents),
+        Some("dxf") => dxf::parse_dxf(contents),
+        _ => obj::parse_obj(contents, curve_samples, read_file),
+    }
+}
+
+/// Extensions `load_model` accepts (ZIP archives need the `zip` feature).
+pub const MODEL_EXTENSIONS: [&str; 5] = ["obj", "ply", "dxf", "stl", "zip"];
+
+/// Lowercase extension of a file name, if it has one.
+pub fn extension_of(name: &str) -> Option<String> {
+    Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The default cube, with every vertex mapped through `f`.
+    fn cube_with(f: impl Fn(Point3) -> Point3) -> Model {
+        let mut model = Model::cube();
+        model.vertices = model.vertices.into_iter().map(f).collect();
+        model
+    }
+
+    #[test]
+    fn center_moves_an_offset_cube_to_the_origin() {
+        let mut model = cube_with(|(x, y, z)| (x + 100.0, y + 100.0, z + 100.0));
+        let center = model.center();
+        assert_eq!(center, Vec3::new(100.0, 100.0, 100.0));
+
+        let (min, max) = model.bounds();
+        assert_eq!(min, Vec3::new(-1.0, -1.0, -1.0));
+        assert_eq!(max, Vec3::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn normalize_scale_fits_the_longest_axis() {
+        // A 10 x 2 x 2 box
+        let mut model = cube_with(|(x, y, z)| (x * 5.0, y, z));
+        let factor = model.normalize_scale();
+        assert!((factor - 0.1).abs() < 1e-6, "{}", factor);
+
+        let extent = {
+            let (min, max) = model.bounds();
+            max - min
+        };
+        assert!((extent.x - 1.0).abs() < 1e-6, "{:?}", extent);
+        assert!((extent.y - 0.2).abs() < 1e-6, "{:?}", extent);
+        assert!((extent.z - 0.2).abs() < 1e-6, "{:?}", extent);
+    }
+
+    #[test]
+    fn normalize_scale_leaves_a_single_point_alone() {
+        let mut model = Model { vertices: vec![(3.0, 4.0, 5.0)], ..Model::default() };
+        assert_eq!(model.normalize_scale(), 1.0);
+        assert_eq!(model.vertices, vec![(3.0, 4.0, 5.0)]);
+    }
+}
@@ -0,0 +1,579 @@
+//! Loader for Wavefront `.obj` models.
+
+use crate::model::{Model, SubModel, EDGE_LINE};
+use m3str3_cube::transformations::Point3;
+use std::collections::HashMap;
+
+/// Parses a 3D model from the contents of a Wavefront `.obj` file.
+/// ---------------------------------------------------------------------
+/// Each line starting with `v` defines a vertex (`v x y z`), optionally
+/// followed by an RGB color in `0..=1` (`v x y z r g b`, as written by
+/// MeshLab and others).
+/// Each line starting with `f` defines a face (`f v1 v2 v3 [v4 ...]`), where
+/// each vertex may also reference a texture coordinate and/or normal
+/// (`v/vt`, `v//vn`, `v/vt/vn`).
+/// Each line starting with `l` defines a polyline (`l v1 v2 [v3 ...]`), where
+/// each vertex may also reference a texture coordinate (`v/vt`).
+/// Indices in `.obj` are 1-based, so we shift them to 0-based for Rust.
+/// Negative indices count back from the latest element of their list
+/// (`-1` is the last vertex defined so far).
+///
+/// As a non-standard convention, a `#color RRGGBB` comment colors the faces
+/// that follow it, up to the next `#color` (`#color none` clears it).
+/// Materials work the same way: `mtllib` loads the diffuse colors of a
+/// material library read through `read_file` (which finds files next to
+/// the OBJ, on disk or in its archive), and `usemtl` colors the faces
+/// that follow with one of them. Faces without a material keep the object
+/// color.
+///
+/// Each `o` or `g` statement starts a new part of the model. Files with
+/// more than one non-empty part list them in `Model::objects`, so they can
+/// be told apart; the parts still share one vertex list.
+///
+/// Free-form curves (`cstype`, `deg`, `curv`, `parm u`, `end`) are only
+/// approximated: each Bezier or B-spline curve is sampled into a polyline of
+/// `curve_samples` segments (per Bezier span) and added as line edges.
+/// Rational weights are ignored, and surfaces (`surf`) are skipped.
+pub fn parse_obj(
+    contents: &str,
+    curve_samples: usize,
+    read_file: &dyn Fn(&str) -> Option<Vec<u8>>,
+) -> Result<Model, String> {
+    let mut model = Model::default();
+    let mut texcoord_count = 0;
+    let mut normal_count = 0;
+    // Color of every `v` line, kept only if at least one vertex has one
+    let mut vertex_colors: Vec<Option<u32>> = Vec::new();
+
+    // Free-form curve state
+    let mut curve_type = CurveType::Bezier;
+    let mut degree = 3;
+    let mut curve: Option<Curve> = None;
+    let mut polylines: Vec<Vec<Point3>> = Vec::new();
+    let mut skipped_surfaces = 0;
+    let mut pending = String::new();
+    let mut face_color: Option<u32> = None;
+    let mut materials: HashMap<String, u32> = HashMap::new();
+    // Parts started by `o`/`g`, beginning with the unnamed one before the first
+    let mut objects = vec![SubModel { name: "default".to_string(), ..SubModel::default() }];
+
+    for line in contents.lines() {
+        // A trailing backslash continues the statement on the next line
+        if let Some(head) = line.trim_end().strip_suffix('\\') {
+            pending.push_str(head);
+            pending.push(' ');
+            continue;
+        }
+        let line = std::mem::take(&mut pending) + line;
+
+        // Face color comment: #color RRGGBB
+        if let Some(value) = line.trim().strip_prefix("#color") {
+            let value = value.trim();
+            face_color = match u32::from_str_radix(value, 16) {
+                Ok(color) if value.len() == 6 => Some(color),
+                _ if value == "none" => None,
+                _ => {
+                    eprintln!("Error parsing face color in line: {}", line);
+                    face_color
+                }
+            };
+            continue;
+        }
+
+        // Strip comments
+        let line = line.split_once('#').map_or(line.as_str(), |(code, _)| code).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.is_empty() {
+            continue;
+        }
+
+        match parts[0] {
+            "v" => {
+                // Vertex line: v x y z [r g b]
+                let coords: Option<Vec<f32>> = parts.iter().skip(1).take(3).map(|c| parse_coordinate(c)).collect();
+                match coords.as_deref() {
+                    Some(&[x, y, z]) => {
+                        model.vertices.push((x, y, z));
+                        let rgb: Option<Vec<f32>> = parts.iter().skip(4).take(3).map(|c| parse_coordinate(c)).collect();
+                        vertex_colors.push(match rgb.as_deref() {
+                            Some(&[r, g, b]) => Some(pack_rgb(r, g, b)),
+                            _ => None,
+                        });
+                    }
+                    // Later indices shift by one, but the rest of the file still loads
+                    _ => eprintln!("Error parsing vertex in line: {}", line),
+                }
+            }
+            "vt" => texcoord_count += 1,
+            "vn" => normal_count += 1,
+            "f" => {
+                // Face line: f v1 v2 v3 [v4 ...], each vertex as v[/vt][/vn]
+                let counts = (model.vertices.len(), texcoord_count, normal_count);
+                let face_indices: Option<Vec<i64>> = parts[1..]
+                    .iter()
+                    .map(|v_str| parse_face_vertex(v_str, counts))
+                    .collect();
+
+                let face_indices = match face_indices {
+                    Some(face_indices) => face_indices,
+                    None => {
+                        eprintln!("Error parsing face indices in line: {}", line);
+                        continue;
+                    }
+                };
+
+                // Check index range
+                if face_indices.iter().any(|&i| i < 0 || i >= model.vertices.len() as i64) {
+                    eprintln!("Index out of range in line: {}", line);
+                    continue;
+                }
+
+                model.add_face(face_indices.into_iter().map(|i| i as usize).collect());
+                if let Some(color) = model.face_colors.last_mut() {
+                    *color = face_color;
+                }
+            }
+            "l" => {
+                // Line element: l v1 v2 [v3 ...], an open polyline, each vertex as v[/vt]
+                let counts = (model.vertices.len(), texcoord_count, 0);
+                let line_indices: Option<Vec<i64>> = parts[1..]
+                    .iter()
+                    .map(|v_str| parse_face_vertex(v_str, counts))
+                    .collect();
+
+                let line_indices = match line_indices {
+                    Some(line_indices) => line_indices,
+                    None => {
+                        eprintln!("Error parsing line indices in line: {}", line);
+                        continue;
+                    }
+                };
+
+                // Check index range
+                if line_indices.iter().any(|&i| i < 0 || i >= model.vertices.len() as i64) {
+                    eprintln!("Index out of range in line: {}", line);
+                    continue;
+                }
+
+                for pair in line_indices.windows(2) {
+                    model.add_edge(pair[0] as usize, pair[1] as usize, EDGE_LINE);
+                }
+            }
+            "cstype" => {
+                // Curve type: cstype [rat] bezier|bspline
+                curve_type = match parts.last().copied() {
+                    Some("bezier") => CurveType::Bezier,
+                    Some("bspline") => CurveType::BSpline,
+                    _ => {
+                        eprintln!("Unsupported curve type in line: {}", line);
+                        CurveType::Unsupported
+                    }
+                };
+            }
+            "deg" => match parts.get(1).and_then(|d| d.parse::<usize>().ok()) {
+                Some(d) if d > 0 => degree = d,
+                _ => eprintln!("Error parsing degree in line: {}", line),
+            },
+            "curv" => {
+                // Curve: curv u0 u1 v1 v2 [v3 ...]
+                let range: Vec<f32> = parts.iter().skip(1).take(2).filter_map(|u| u.parse().ok()).collect();
+                let control: Option<Vec<Point3>> = parts.iter()
+                    .skip(3)
+                    .map(|v_str| {
+                        let i = resolve_index(v_str, model.vertices.len())?;
+                        model.vertices.get(usize::try_from(i).ok()?).copied()
+                    })
+                    .collect();
+
+                curve = match control {
+                    Some(control) if range.len() == 2 && control.len() > degree => Some(Curve {
+                        kind: curve_type,
+                        degree,
+                        range: (range[0], range[1]),
+                        control,
+                        knots: Vec::new(),
+                    }),
+                    _ => {
+                        eprintln!("Error parsing curve in line: {}", line);
+                        None
+                    }
+                };
+            }
+            "parm" => {
+                // Knot vector of the current curve: parm u k1 k2 ...
+                if let (Some(curve), Some("u")) = (curve.as_mut(), parts.get(1).copied()) {
+                    curve.knots = parts[2..].iter().filter_map(|k| k.parse().ok()).collect();
+                }
+            }
+            "o" | "g" => {
+                // New object or group: o name, g name [name ...]
+                let (faces, edges) = (model.faces.len(), model.edges.len());
+                if let Some(current) = objects.last_mut() {
+                    (current.faces.end, current.edges.end) = (faces, edges);
+                }
+                let name = if parts.len() > 1 { parts[1..].join(" ") } else { "default".to_string() };
+                objects.push(SubModel { name, faces: faces..faces, edges: edges..edges });
+            }
+            "mtllib" => {
+                // Material libraries: mtllib file1.mtl [file2.mtl ...]
+                for name in &parts[1..] {
+                    match read_file(name).map(String::from_utf8) {
+                        Some(Ok(library)) => materials.extend(parse_mtl(&library)),
+                        Some(Err(_)) => eprintln!("Error loading material library {}: not valid UTF-8 text", name),
+                        None => eprintln!("Error loading material library {}: file not found", name),
+                    }
+                }
+            }
+            "usemtl" => {
+                // Material of the faces that follow: usemtl name
+                let name = parts.get(1).copied().unwrap_or_default();
+                face_color = materials.get(name).copied();
+                if face_color.is_none() {
+                    eprintln!("Unknown material (or one without a Kd color) in line: {}", line);
+                }
+            }
+            "surf" => skipped_surfaces += 1,
+            "end" => {
+                if let Some(curve) = curve.take() {
+                    match curve.sample(curve_samples) {
+                        Some(points) => polylines.push(points),
+                        None => eprintln!("Could not sample curve ending at line: {}", line),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !polylines.is_empty() {
+        println!("Approximated {} free-form curve(s) as polylines", polylines.len());
+    }
+    if skipped_surfaces > 0 {
+        eprintln!("Skipped {} free-form surface(s), which aren't supported", skipped_surfaces);
+    }
+
+    // Sampled points are appended last so they don't shift relative indices
+    for points in polylines {
+        let first = model.vertices.len();
+        model.vertices.extend(points);
+        for i in first + 1..model.vertices.len() {
+            model.add_edge(i - 1, i, EDGE_LINE);
+        }
+    }
+
+    // Close the last part (curves added above go with it) and keep the parts
+    // only if there are several with geometry
+    if let Some(current) = objects.last_mut() {
+        (current.faces.end, current.edges.end) = (model.faces.len(), model.edges.len());
+    }
+    objects.retain(|object| !object.faces.is_empty() || !object.edges.is_empty());
+    if objects.len() > 1 {
+        model.objects = objects;
+    }
+
+    if vertex_colors.iter().any(Option::is_some) {
+        vertex_colors.resize(model.vertices.len(), None);
+        model.vertex_colors = vertex_colors;
+    }
+
+    Ok(model)
+}
+
+/// Reads the diffuse colors of a `.mtl` material library.
+/// ---------------------------------------------------------------------
+/// Each material starts with `newmtl name`; its `Kd r g b` line (channels
+/// in `0..=1`) gives its color. Materials without `Kd` are left out, and
+/// every other statement (textures, specular, transparency) is ignored.
+fn parse_mtl(contents: &str) -> HashMap<String, u32> {
+    let mut materials = HashMap::new();
+    let mut current: Option<&str> = None;
+    for line in contents.lines() {
+        let line = line.split_once('#').map_or(line, |(code, _)| code).trim();
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts.as_slice() {
+            ["newmtl", name, ..] => current = Some(name),
+            ["Kd", r, g, b, ..] => {
+                let Some(name) = current else { continue };
+                match (parse_coordinate(r), parse_coordinate(g), parse_coordinate(b)) {
+                    (Some(r), Some(g), Some(b)) => {
+                        materials.insert(name.to_string(), pack_rgb(r, g, b));
+                    }
+                    _ => eprintln!("Error parsing diffuse color in line: {}", line),
+                }
+            }
+            _ => {}
+        }
+    }
+    materials
+}
+
+/// Packs an RGB color with channels in `0..=1` as `0xRRGGBB`.
+fn pack_rgb(r: f32, g: f32, b: f32) -> u32 {
+    let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u32;
+    (channel(r) << 16) | (channel(g) << 8) | channel(b)
+}
+
+/// Basis of a free-form curve, as given by `cstype`.
+#[derive(Clone, Copy)]
+enum CurveType {
+    Bezier,
+    BSpline,
+    Unsupported,
+}
+
+/// A free-form curve collected between `curv` and `end`.
+struct Curve {
+    kind: CurveType,
+    degree: usize,
+    /// Parameter range `u0..u1` to draw.
+    range: (f32, f32),
+    control: Vec<Point3>,
+    /// Knot vector from `parm u` (empty if none was given).
+    knots: Vec<f32>,
+}
+
+impl Curve {
+    /// Samples the curve into a polyline, or `None` if it can't be evaluated.
+    fn sample(&self, samples: usize) -> Option<Vec<Point3>> {
+        match self.kind {
+            CurveType::Bezier => self.sample_bezier(samples),
+            CurveType::BSpline => self.sample_bspline(samples),
+            CurveType::Unsupported => None,
+        }
+    }
+
+    /// Splits the control points into spans of `degree + 1` points sharing
+    /// their endpoints and samples each span with de Casteljau's algorithm.
+    fn sample_bezier(&self, samples: usize) -> Option<Vec<Point3>> {
+        let d = self.degree;
+        if !(self.control.len() - 1).is_multiple_of(d) {
+            return None;
+        }
+
+        let mut points = vec![self.control[0]];
+        for span in self.control.windows(d + 1).step_by(d) {
+            for i in 1..=samples {
+                points.push(de_casteljau(span, i as f32 / samples as f32));
+            }
+        }
+        Some(points)
+    }
+
+    /// Samples `range` with de Boor's algorithm. Without a usable `parm u`
+    /// knot vector a clamped uniform one over `range` is assumed.
+    fn sample_bspline(&self, samples: usize) -> Option<Vec<Point3>> {
+        let (d, n) = (self.degree, self.control.len());
+        let (knots, (u0, u1)) = if self.knots.len() == n + d + 1 {
+            (self.knots.clone(), self.range)
+        } else {
+            let spans = (n - d) as f32;
+            let knots = (0..n + d + 1)
+                .map(|i| i.saturating_sub(d).min(n - d) as f32 / spans)
+                .collect();
+            (knots, (0.0, 1.0))
+        };
+
+        // Clamp the drawn range to where the curve is defined
+        let u0 = u0.max(knots[d]);
+        let u1 = u1.min(knots[n]);
+        if u0 >= u1 {
+            return None;
+        }
+
+        let points = (0..=samples)
+            .map(|i| de_boor(&self.control, &knots, d, u0 + (u1 - u0) * i as f32 / samples as f32))
+            .collect();
+        Some(points)
+    }
+}
+
+/// Evaluates a Bezier span at `t` in `[0, 1]`.
+fn de_casteljau(span: &[Point3], t: f32) -> Point3 {
+    let mut points = span.to_vec();
+    for level in 1..points.len() {
+        for i in 0..points.len() - level {
+            points[i] = lerp(points[i], points[i + 1], t);
+        }
+    }
+    points[0]
+}
+
+/// Evaluates a B-spline of degree `d` at `u` within `knots[d]..=knots[n]`.
+fn de_boor(control: &[Point3], knots: &[f32], d: usize, u: f32) -> Point3 {
+    let n = control.len();
+    // Knot span containing u (the last span includes its end)
+    let k = (d..n).rev().find(|&k| knots[k] <= u).unwrap_or(d);
+
+    let mut points: Vec<Point3> = control[k - d..=k].to_vec();
+    for r in 1..=d {
+        for j in (r..=d).rev() {
+            let i = k - d + j;
+            let span = knots[i + d + 1 - r] - knots[i];
+            let alpha = if span > 0.0 { (u - knots[i]) / span } else { 0.0 };
+            points[j] = lerp(points[j - 1], points[j], alpha);
+        }
+    }
+    points[d]
+}
+
+fn lerp(a: Point3, b: Point3, t: f32) -> Point3 {
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t, a.2 + (b.2 - a.2) * t)
+}
+
+/// Parses a vertex coordinate. Rust's float syntax already covers a leading
+/// `+`, a leading or trailing dot (`.5`, `5.`) and either exponent case
+/// (`1e3`, `2.0E+1`); Fortran-style `D` exponents (`1.5D+03`) are accepted
+/// too. Non-finite values (`inf`, `nan`) are rejected.
+fn parse_coordinate(token: &str) -> Option<f32> {
+    let value: f32 = match token.parse() {
+        Ok(value) => value,
+        Err(_) => token.replace(['d', 'D'], "e").parse().ok()?,
+    };
+    value.is_finite().then_some(value)
+}
+
+/// Resolves a 1-based OBJ index into a 0-based one. Negative indices count
+/// back from the end of a list currently holding `count` elements, so `-1`
+/// resolves to `count - 1`. The result may still be out of range and must be
+/// checked by the caller. Returns `None` for non-numeric tokens and `0`.
+fn resolve_index(token: &str, count: usize) -> Option<i64> {
+    match token.parse::<i64>().ok()? {
+        0 => None,
+        idx if idx > 0 => Some(idx - 1),
+        idx => Some(count as i64 + idx),
+    }
+}
+
+/// Parses one vertex of a face or line (`v`, `v/vt`, `v//vn` or `v/vt/vn`) and returns
+/// its 0-based vertex index. The texture and normal references aren't used
+/// yet, so they're only checked to be nonzero integers, not to be in range
+/// of their lists (`counts` = vertices, texcoords, normals).
+fn parse_face_vertex(token: &str, counts: (usize, usize, usize)) -> Option<i64> {
+    let mut components = token.split('/');
+    let vertex = resolve_index(components.next()?, counts.0)?;
+
+    for count in [counts.1, counts.2] {
+        match components.next() {
+            None | Some("") => {}
+            Some(component) => {
+                resolve_index(component, count)?;
+            }
+        }
+    }
+    if components.next().is_some() {
+        return None;
+    }
+
+    Some(vertex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Model {
+        parse_obj(contents, 16, &|_| None).unwrap()
+    }
+
+    /// A `v x y z` line for each of the first `count` points on the X axis.
+    fn vertex_lines(count: usize) -> String {
+        (0..count).map(|i| format!("v {} 0 0\n", i)).collect()
+    }
+
+    #[test]
+    fn slash_separated_face_indices() {
+        let model = parse(&(vertex_lines(9) + "f 1/2/3 4/5/6 7/8/9\n"));
+        assert_eq!(model.faces, vec![vec![0, 3, 6]]);
+        assert_eq!(model.edges, vec![(0, 3), (3, 6), (6, 0)]);
+    }
+
+    #[test]
+    fn every_slash_form() {
+        let model = parse(&(vertex_lines(4) + "f 1 2/1 3//1 4/1/1\n"));
+        assert_eq!(model.faces, vec![vec![0, 1, 2, 3]]);
+    }
+
+    #[test]
+    fn negative_slash_separated_face_indices() {
+        let contents = vertex_lines(3) + "vt 0 0\nvt 1 0\nvt 0 1\nvn 0 0 1\nf -3/-3/-1 -2/-2/-1 -1/-1/-1\n";
+        let model = parse(&contents);
+        assert_eq!(model.faces, vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn unusual_coordinate_formats() {
+        assert_eq!(parse_coordinate("+1.5e-3"), Some(1.5e-3));
+        assert_eq!(parse_coordinate("-2.0E+1"), Some(-20.0));
+        assert_eq!(parse_coordinate(".5"), Some(0.5));
+        assert_eq!(parse_coordinate("1.0d2"), Some(100.0));
+        assert_eq!(parse_coordinate("1.2.3"), None);
+        assert_eq!(parse_coordinate("1e"), None);
+        assert_eq!(parse_coordinate("nan"), None);
+
+        let model = parse("v +1.5e-3 -2.0E+1 .5\n");
+        assert_eq!(model.vertices, vec![(1.5e-3, -20.0, 0.5)]);
+    }
+
+    #[test]
+    fn positive_and_negative_indices() {
+        let contents = vertex_lines(4) + "f 1 -3 3\nf -4 3 -1\n" + &vertex_lines(1) + "l 5 -4\nl -1 1\n";
+        let model = parse(&contents);
+        assert_eq!(model.faces, vec![vec![0, 1, 2], vec![0, 2, 3]]);
+
+        let lines: Vec<(usize, usize)> = model
+            .edges
+            .iter()
+            .zip(&model.edge_flags)
+            .filter(|&(_, &flags)| flags & EDGE_LINE != 0)
+            .map(|(&edge, _)| edge)
+            .collect();
+        assert_eq!(lines, vec![(4, 1), (4, 0)]);
+    }
+
+    #[test]
+    fn out_of_range_negative_indices_are_skipped() {
+        let contents = vertex_lines(3) + "f -4 -2 -1\nl -1 -5\nf 1 2 3\n";
+        let model = parse(&contents);
+        assert_eq!(model.faces, vec![vec![0, 1, 2]]);
+        assert_eq!(model.edges.len(), 3);
+    }
+
+    #[test]
+    fn mixed_colored_and_uncolored_vertices() {
+        let model = parse(include_str!("../models/colored_cube.obj"));
+        assert_eq!(
+            model.vertex_colors,
+            vec![
+                Some(0x000000),
+                Some(0xFF0000),
+                Some(0xFFFF00),
+                Some(0x00FF00),
+                Some(0x0000FF),
+                None,
+                Some(0xFFFFFF),
+                None,
+            ]
+        );
+
+        // Edges between two colored vertices mix them; the rest keep the object color
+        let edge_colors = model.edge_colors();
+        let color_of = |a: usize, b: usize| {
+            let e = model.edges.iter().position(|&edge| edge == (a, b) || edge == (b, a)).unwrap();
+            edge_colors[e]
+        };
+        assert_eq!(color_of(0, 1), Some(0x7F0000));
+        assert_eq!(color_of(4, 5), None);
+        assert_eq!(color_of(6, 7), None);
+    }
+
+    #[test]
+    fn uncolored_files_have_no_vertex_colors() {
+        let model = parse(&(vertex_lines(3) + "f 1 2 3\n"));
+        assert!(model.vertex_colors.is_empty());
+        assert!(model.edge_colors().is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+//! Loader for ASCII Stanford `.ply` meshes.
+
+use crate::model::Model;
+
+/// Per-vertex scalar properties read into `Model::intensity`, in order of preference.
+const SCALAR_PROPERTIES: [&str; 4] = ["intensity", "quality", "confidence", "scalar"];
+
+/// A property declared in the PLY header.
+enum Property {
+    Scalar(String),
+    List(String),
+}
+
+/// An element declared in the PLY header (`element vertex 8`).
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// Values read for one property of one element.
+enum Value {
+    Scalar(f32),
+    List(Vec<usize>),
+}
+
+/// Parses a 3D model from the contents of an ASCII `.ply` file.
+/// ---------------------------------------------------------------------
+/// The header lists the elements (`vertex`, `face`, ...) with their counts
+/// and properties; the body then holds one line per element in that order.
+/// Vertices read their `x y z` properties, faces read their index list, and
+/// everything else (colors, normals, unknown elements) is skipped.
+pub fn parse_ply(contents: &str) -> Result<Model, String> {
+    let mut lines = contents.lines();
+
+    if lines.next().map(str::trim) != Some("ply") {
+        return Err("Missing 'ply' magic number".to_string());
+    }
+
+    // Header
+    let mut elements: Vec<Element> = Vec::new();
+    loop {
+        let line = lines.next().ok_or("Unexpected end of PLY header")?;
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts.as_slice() {
+            ["format", "ascii", ..] => {}
+            ["format", format, ..] => {
+                return Err(format!("Unsupported PLY format: {}", format));
+            }
+            ["element", name, count] => {
+                let count = count
+                    .parse()
+                    .map_err(|_| format!("Invalid element count in line: {}", line))?;
+                elements.push(Element { name: name.to_string(), count, properties: Vec::new() });
+            }
+            ["property", "list", _, _, name] => {
+                let element = elements.last_mut().ok_or("Property declared before any element")?;
+                element.properties.push(Property::List(name.to_string()));
+            }
+            ["property", _, name] => {
+                let element = elements.last_mut().ok_or("Property declared before any element")?;
+                element.properties.push(Property::Scalar(name.to_string()));
+            }
+            ["end_header"] => break,
+            _ => {}
+        }
+    }
+
+    let mut model = Model::default();
+
+    // Body
+    for element in &elements {
+        let property_index = |wanted: &str| {
+            element.properties.iter().position(|p| matches!(p, Property::Scalar(name) if name == wanted))
+        };
+        let (x, y, z) = (property_index("x"), property_index("y"), property_index("z"));
+        let scalar = SCALAR_PROPERTIES.iter().find_map(|name| property_index(name));
+        let list = element
+            .properties
+            .iter()
+            .position(|p| matches!(p, Property::List(name) if name == "vertex_indices" || name == "vertex_index"));
+
+        for _ in 0..element.count {
+            let line = lines.next().ok_or_else(|| format!("Unexpected end of '{}' data", element.name))?;
+            let values = parse_values(&element.properties, line)
+                .ok_or_else(|| format!("Malformed '{}' line: {}", element.name, line))?;
+
+            match element.name.as_str() {
+                "vertex" => {
+                    let (Some(x), Some(y), Some(z)) = (x, y, z) else {
+                        return Err("Vertex element is missing x/y/z properties".to_string());
+                    };
+                    model.vertices.push((scalar_at(&values, x), scalar_at(&values, y), scalar_at(&values, z)));
+                    if let Some(scalar) = scalar {
+                        model.intensity.push(scalar_at(&values, scalar));
+                    }
+                }
+                "face" => {
+                    let Some(Value::List(face_indices)) = list.map(|i| &values[i]) else {
+                        continue;
+                    };
+
+                    // Check index range
+                    if face_indices.iter().any(|&i| i >= model.vertices.len()) {
+                        eprintln!("Index out of range in line: {}", line);
+                        continue;
+                    }
+
+                    model.add_face(face_indices.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(model)
+}
+
+/// Splits a body line into one value per declared property.
+fn parse_values(properties: &[Property], line: &str) -> Option<Vec<Value>> {
+    let mut tokens = line.split_whitespace();
+    properties
+        .iter()
+        .map(|property| match property {
+            Property::Scalar(_) => tokens.next()?.parse().ok().map(Value::Scalar),
+            Property::List(_) => {
+                let count: usize = tokens.next()?.parse().ok()?;
+                let indices: Option<Vec<usize>> =
+                    (0..count).map(|_| tokens.next()?.parse().ok()).collect();
+                indices.map(Value::List)
+            }
+        })
+        .collect()
+}
+
+fn scalar_at(values: &[Value], index: usize) -> f32 {
+    match values[index] {
+        Value::Scalar(v) => v,
+        Value::List(_) => 0.0,
+    }
+}
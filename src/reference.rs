@@ -0,0 +1,61 @@
+//! Reference images (photos, blueprints) drawn behind the model.
+
+use crate::rendering;
+
+/// A decoded image kept as packed `0xRRGGBB` pixels.
+pub struct ReferenceImage {
+    width: usize,
+    height: usize,
+    pixels: Vec<u32>,
+}
+
+impl ReferenceImage {
+    /// Loads any image format the `image` crate was built with (PNG, JPEG).
+    pub fn load(file_path: &str) -> Result<ReferenceImage, String> {
+        let image = image::open(file_path)
+            .map_err(|e| format!("Could not open image: {}", e))?
+            .to_rgb8();
+        let pixels = image
+            .pixels()
+            .map(|p| ((p[0] as u32) << 16) | ((p[1] as u32) << 8) | p[2] as u32)
+            .collect();
+
+        Ok(ReferenceImage {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            pixels,
+        })
+    }
+
+    /// Blends the image over the `buffer`, centered and with `opacity`.
+    /// ---------------------------------------------------------------------
+    /// At `scale` 1 the image fits the buffer height; the aspect ratio is
+    /// kept and pixels are sampled nearest-neighbor. Parts falling outside
+    /// the buffer are cropped.
+    pub fn draw(&self, buffer: &mut [u32], width: usize, height: usize, scale: f32, blend: rendering::Blend) {
+        if self.height == 0 || height == 0 {
+            return;
+        }
+        // Screen pixels per image pixel
+        let zoom = scale * height as f32 / self.height as f32;
+        let (drawn_w, drawn_h) = (self.width as f32 * zoom, self.height as f32 * zoom);
+        let left = (width as f32 - drawn_w) / 2.0;
+        let top = (height as f32 - drawn_h) / 2.0;
+
+        for y in 0..height {
+            let iy = ((y as f32 + 0.5 - top) / zoom).floor();
+            if iy < 0.0 || iy >= self.height as f32 {
+                continue;
+            }
+            for x in 0..width {
+                let ix = ((x as f32 + 0.5 - left) / zoom).floor();
+                if ix < 0.0 || ix >= self.width as f32 {
+                    continue;
+                }
+                let src = self.pixels[iy as usize * self.width + ix as usize];
+                let dst = &mut buffer[y * width + x];
+                *dst = rendering::blend_color(*dst, src, blend.alpha, blend.linear);
+            }
+        }
+    }
+}
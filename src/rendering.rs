@@ -1,3 +1,5 @@
+use font8x8::legacy::BASIC_LEGACY;
+
 /// Draws a line onto the `buffer` using the Bresenham algorithm in 2D.
 /// The color is specified as a 32-bit ARGB value (`u32`).
 ///
@@ -19,8 +21,19 @@ pub fn draw_line(
     (x1, y1): (usize, usize),
     color: u32,
 ) {
-    let (x1, y1) = (x1 as i32, y1 as i32);
-    let (mut x0, mut y0) = (x0 as i32, y0 as i32);
+    bresenham((x0, y0), (x1, y1), |x, y, _| {
+        if x >= 0 && x < width as i32 && y >= 0 && y < height as i32 {
+            buffer[(y as usize) * width + (x as usize)] = color;
+        }
+    });
+}
+
+/// Walks the Bresenham line from `start` to `end`, calling `plot` with every
+/// pixel `(x, y)` on it and how far along the line it is, from 0 at `start`
+/// to 1 at `end`. Pixels are not clipped to any buffer.
+fn bresenham(start: (usize, usize), end: (usize, usize), mut plot: impl FnMut(i32, i32, f32)) {
+    let (x1, y1) = (end.0 as i32, end.1 as i32);
+    let (mut x0, mut y0) = (start.0 as i32, start.1 as i32);
 
     let dx = (x1 - x0).abs();
     let sx = if x0 < x1 { 1 } else { -1 };
@@ -28,12 +41,12 @@ pub fn draw_line(
     let sy = if y0 < y1 { 1 } else { -1 };
 
     let mut err = dx + dy;
+    // One step per pixel along the major axis
+    let steps = dx.max(-dy).max(1) as f32;
+    let mut step = 0;
 
     loop {
-        if x0 >= 0 && x0 < width as i32 && y0 >= 0 && y0 < height as i32 {
-            let index = (y0 as usize) * width + (x0 as usize);
-            buffer[index] = color;
-        }
+        plot(x0, y0, step as f32 / steps);
 
         if x0 == x1 && y0 == y1 {
             break;
@@ -49,5 +62,704 @@ pub fn draw_line(
             err += dx;
             y0 += sy;
         }
+        step += 1;
+    }
+}
+
+/// Draws a Bresenham line whose color fades from `start_color` at `start`
+/// to `end_color` at `end`, for edges between vertices of different colors.
+/// Each pixel's color is interpolated channel-wise by its step along the line.
+pub fn draw_line_gradient(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    start: (usize, usize),
+    end: (usize, usize),
+    start_color: u32,
+    end_color: u32,
+) {
+    bresenham(start, end, |x, y, t| {
+        if x >= 0 && x < width as i32 && y >= 0 && y < height as i32 {
+            buffer[(y as usize) * width + (x as usize)] = blend_color(start_color, end_color, t, false);
+        }
+    });
+}
+
+/// Draws a 1 pixel line between subpixel endpoints (pixel `i` spans
+/// `[i, i + 1)`), so the pixels follow the exact line instead of the one
+/// between the rounded endpoints and slowly moving edges don't jitter.
+///
+/// Steps one pixel at a time along the major axis and, at each pixel center
+/// \( c \), plots the pixel containing the line there:
+/// $$ y(c) = y_0 + (c - x_0) \frac{\Delta y}{\Delta x} $$
+/// (with x and y swapped for steep lines), clamped to the segment's ends.
+pub fn draw_line_subpixel(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    (x0, y0): (f32, f32),
+    (x1, y1): (f32, f32),
+    color: u32,
+) {
+    let steep = (y1 - y0).abs() > (x1 - x0).abs();
+    // Work along the major axis as (a, b), a increasing
+    let (a0, b0, a1, b1) = if steep { (y0, x0, y1, x1) } else { (x0, y0, x1, y1) };
+    let ((a0, b0), (a1, b1)) = if a0 <= a1 { ((a0, b0), (a1, b1)) } else { ((a1, b1), (a0, b0)) };
+    let slope = if a1 > a0 { (b1 - b0) / (a1 - a0) } else { 0.0 };
+
+    let (first, last) = (a0.floor() as i64, a1.floor() as i64);
+    for i in first..=last {
+        let c = (i as f32 + 0.5).clamp(a0, a1);
+        let j = (b0 + (c - a0) * slope).floor() as i64;
+        let (x, y) = if steep { (j, i) } else { (i, j) };
+        if x >= 0 && (x as usize) < width && y >= 0 && (y as usize) < height {
+            buffer[y as usize * width + x as usize] = color;
+        }
+    }
+}
+
+/// Draws a line `thickness` pixels wide by stamping a round brush,
+/// `thickness` pixels across, at every Bresenham step. Since the brush is
+/// round, the ends are rounded too and edges meeting at a vertex join
+/// without gaps at the corners.
+/// Useful on high-DPI displays, where single pixel lines are hard to see.
+pub fn draw_line_thick(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    start: (usize, usize),
+    end: (usize, usize),
+    color: u32,
+    thickness: usize,
+) {
+    // Brush offsets lo..=hi around the line, centered between pixels for even widths
+    let (lo, hi) = (-((thickness.max(1) as i32 - 1) / 2), thickness.max(1) as i32 / 2);
+    let center = (lo + hi) as f32 / 2.0;
+    let radius = thickness as f32 / 2.0;
+    let brush: Vec<(i32, i32)> = (lo..=hi)
+        .flat_map(|oy| (lo..=hi).map(move |ox| (ox, oy)))
+        .filter(|&(ox, oy)| (ox as f32 - center).powi(2) + (oy as f32 - center).powi(2) <= radius * radius)
+        .collect();
+
+    bresenham(start, end, |x0, y0, _| {
+        for &(ox, oy) in &brush {
+            let (x, y) = (x0 + ox, y0 + oy);
+            if x >= 0 && x < width as i32 && y >= 0 && y < height as i32 {
+                buffer[(y as usize) * width + (x as usize)] = color;
+            }
+        }
+    });
+}
+
+/// Spacing and jitter settings for `draw_stippled_line`.
+#[derive(Clone, Copy)]
+pub struct Stipple {
+    /// Distance in pixels between consecutive dots.
+    pub spacing: f32,
+    /// Random offset applied to each dot, as a fraction of `spacing` (0 = regular).
+    pub jitter: f32,
+}
+
+/// Draws a dotted line onto the `buffer` by plotting single pixels at regular
+/// intervals along the segment, producing a stippled/sketch look.
+///
+/// The segment is walked parametrically: dot `i` is placed at distance
+/// \( d_i = i \cdot \text{spacing} + j_i \) from the start, where \( j_i \) is a
+/// pseudo-random offset in \( [-\text{jitter}, \text{jitter}] \cdot \text{spacing} / 2 \).
+/// The offsets are derived from the endpoints, so a still line keeps its pattern.
+pub fn draw_stippled_line(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    (x0, y0): (usize, usize),
+    (x1, y1): (usize, usize),
+    color: u32,
+    stipple: Stipple,
+) {
+    let (fx0, fy0) = (x0 as f32, y0 as f32);
+    let (dx, dy) = (x1 as f32 - fx0, y1 as f32 - fy0);
+    let length = (dx * dx + dy * dy).sqrt();
+    let spacing = stipple.spacing.max(1.0);
+    let dots = (length / spacing) as usize;
+
+    let mut seed = (x0 ^ (y0 << 8) ^ (x1 << 16) ^ (y1 << 24)) as u32 | 1;
+
+    for i in 0..=dots {
+        let mut d = i as f32 * spacing;
+        if stipple.jitter > 0.0 {
+            // xorshift32, mapped to [-0.5, 0.5)
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            let r = (seed as f32 / u32::MAX as f32) - 0.5;
+            d += r * stipple.jitter * spacing;
+        }
+
+        let t = if length > 0.0 { (d / length).clamp(0.0, 1.0) } else { 0.0 };
+        let x = (fx0 + dx * t).round() as i32;
+        let y = (fy0 + dy * t).round() as i32;
+
+        if x >= 0 && x < width as i32 && y >= 0 && y < height as i32 {
+            buffer[(y as usize) * width + (x as usize)] = color;
+        }
+    }
+}
+
+/// Draws `text` onto the `buffer` with its top-left corner at `(x, y)`, using
+/// an 8x8 bitmap font (ASCII only; other characters are drawn as `?`).
+///
+/// Each glyph is 8 bytes, one per row; bit `i` of a row is the pixel at column `i`.
+/// Pixels outside the buffer are skipped, so text may run off the edges.
+pub fn draw_text(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    (x, y): (usize, usize),
+    text: &str,
+    color: u32,
+) {
+    for (n, c) in text.chars().enumerate() {
+        let glyph = BASIC_LEGACY[if c.is_ascii() { c as usize } else { '?' as usize }];
+        let gx = x + n * 8;
+
+        for (row, bits) in glyph.iter().enumerate() {
+            let py = y + row;
+            if py >= height {
+                break;
+            }
+            for col in 0..8 {
+                let px = gx + col;
+                if bits & (1 << col) != 0 && px < width {
+                    buffer[py * width + px] = color;
+                }
+            }
+        }
+    }
+}
+
+/// Fills the rectangle with top-left corner `(x, y)` and size `w` x `h`
+/// with `color`, clipped to the buffer. The corner may lie off the buffer on
+/// any side.
+pub fn fill_rect(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    rect: (isize, isize, usize, usize),
+    color: u32,
+) {
+    let ((x0, x1), (y0, y1)) = clip_rect(width, height, rect);
+    for row in y0..y1 {
+        buffer[row * width + x0..row * width + x1].fill(color);
+    }
+}
+
+/// Like `fill_rect`, but blends `color` over the existing pixels (see `blend_color`).
+pub fn fill_rect_alpha(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    rect: (isize, isize, usize, usize),
+    color: u32,
+    blend: Blend,
+) {
+    let ((x0, x1), (y0, y1)) = clip_rect(width, height, rect);
+    for row in y0..y1 {
+        for pixel in &mut buffer[row * width + x0..row * width + x1] {
+            *pixel = blend_color(*pixel, color, blend.alpha, blend.linear);
+        }
+    }
+}
+
+/// The column and row spans `(start, end)` of the rectangle `(x, y, w, h)`
+/// inside a `width` x `height` buffer; empty spans if it lies outside.
+fn clip_rect(width: usize, height: usize, (x, y, w, h): (isize, isize, usize, usize)) -> ((usize, usize), (usize, usize)) {
+    let clip = |start: isize, size: usize, limit: usize| {
+        let limit = limit as isize;
+        (start.clamp(0, limit) as usize, start.saturating_add_unsigned(size).clamp(0, limit) as usize)
+    };
+    (clip(x, w, width), clip(y, h, height))
+}
+
+/// Draws a bar chart of `counts` inside the rectangle `(x, y, w, h)`, bars
+/// growing up from the bottom edge and scaled so the largest fills the height.
+/// Each bar is `w / counts.len()` pixels wide, with a 1 pixel gap.
+pub fn draw_bars(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    (x, y, w, h): (usize, usize, usize, usize),
+    counts: &[usize],
+    color: u32,
+) {
+    let peak = counts.iter().copied().max().unwrap_or(0).max(1);
+    let bar_width = (w / counts.len().max(1)).max(1);
+    let bottom = y + h;
+
+    for (i, &count) in counts.iter().enumerate() {
+        let bar_height = count * h / peak;
+        let bar = ((x + i * bar_width) as isize, (bottom - bar_height) as isize, bar_width.saturating_sub(1).max(1), bar_height);
+        fill_rect(buffer, width, height, bar, color);
+    }
+}
+
+/// Marks a pixel still holding the cleared background: frames are cleared to
+/// `background | BACKGROUND_TAG`, and since every drawing function writes
+/// whole `0xRRGGBB` pixels, the tag survives only where nothing was drawn.
+/// minifb ignores the top byte, so the tag doesn't show on screen.
+pub const BACKGROUND_TAG: u32 = 0x0100_0000;
+
+/// Returns black or white, whichever reads better on top of `background`.
+/// Uses the Rec. 601 luma \( Y = 0.299 R + 0.587 G + 0.114 B \).
+pub fn contrast_color(background: u32) -> u32 {
+    let r = ((background >> 16) & 0xFF) as f32;
+    let g = ((background >> 8) & 0xFF) as f32;
+    let b = (background & 0xFF) as f32;
+    if 0.299 * r + 0.587 * g + 0.114 * b > 128.0 {
+        0x000000
+    } else {
+        0xFFFFFF
+    }
+}
+
+/// Fills a solid triangle onto the `buffer`.
+///
+/// Every pixel of the triangle's bounding box (clipped to the buffer) is tested
+/// against the three edge functions
+/// $$ E_{ab}(p) = (b_x - a_x)(p_y - a_y) - (b_y - a_y)(p_x - a_x) $$
+/// and is filled when all three have the same sign as the triangle's signed
+/// area, so both windings are filled. Degenerate (zero-area) triangles are skipped.
+pub fn fill_triangle(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    p0: (usize, usize),
+    p1: (usize, usize),
+    p2: (usize, usize),
+    color: u32,
+) {
+    let to_i64 = |(x, y): (usize, usize)| (x as i64, y as i64);
+    let (a, b, c) = (to_i64(p0), to_i64(p1), to_i64(p2));
+    let edge = |(ax, ay): (i64, i64), (bx, by): (i64, i64), (px, py): (i64, i64)| {
+        (bx - ax) * (py - ay) - (by - ay) * (px - ax)
+    };
+
+    let area = edge(a, b, c);
+    if area == 0 {
+        return;
+    }
+
+    let min_x = a.0.min(b.0).min(c.0).max(0);
+    let max_x = a.0.max(b.0).max(c.0).min(width as i64 - 1);
+    let min_y = a.1.min(b.1).min(c.1).max(0);
+    let max_y = a.1.max(b.1).max(c.1).min(height as i64 - 1);
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let p = (x, y);
+            let (w0, w1, w2) = (edge(b, c, p), edge(c, a, p), edge(a, b, p));
+            let inside = if area > 0 {
+                w0 >= 0 && w1 >= 0 && w2 >= 0
+            } else {
+                w0 <= 0 && w1 <= 0 && w2 <= 0
+            };
+            if inside {
+                buffer[(y as usize) * width + (x as usize)] = color;
+            }
+        }
+    }
+}
+
+/// Per-pixel camera-space depth of the nearest filled face drawn so far, for
+/// hidden-surface removal. Starts out at `f32::INFINITY` everywhere.
+pub struct DepthBuffer {
+    width: usize,
+    height: usize,
+    depth: Vec<f32>,
+}
+
+impl DepthBuffer {
+    pub fn new(width: usize, height: usize) -> DepthBuffer {
+        DepthBuffer { width, height, depth: vec![f32::INFINITY; width * height] }
+    }
+
+    /// Fills a solid triangle onto `buffer` (which must have the depth buffer's
+    /// size), writing only the pixels nearer than the stored depth.
+    ///
+    /// The corners are `(x, y, z)` with `z` the camera-space depth. Depth is not
+    /// linear across the screen, but its reciprocal is, so `1 / z` is
+    /// interpolated with the barycentric weights
+    /// $$ \frac{1}{z} = \frac{w_0}{z_0} + \frac{w_1}{z_1} + \frac{w_2}{z_2} $$
+    /// Coverage follows `fill_triangle`: both windings, degenerate triangles skipped.
+    pub fn fill_triangle(
+        &mut self,
+        buffer: &mut [u32],
+        p0: (usize, usize, f32),
+        p1: (usize, usize, f32),
+        p2: (usize, usize, f32),
+        color: u32,
+    ) {
+        let to_i64 = |(x, y, _): (usize, usize, f32)| (x as i64, y as i64);
+        let (a, b, c) = (to_i64(p0), to_i64(p1), to_i64(p2));
+        let edge = |(ax, ay): (i64, i64), (bx, by): (i64, i64), (px, py): (i64, i64)| {
+            (bx - ax) * (py - ay) - (by - ay) * (px - ax)
+        };
+
+        let area = edge(a, b, c);
+        if area == 0 {
+            return;
+        }
+        let inverse = (1.0 / p0.2, 1.0 / p1.2, 1.0 / p2.2);
+
+        let min_x = a.0.min(b.0).min(c.0).max(0);
+        let max_x = a.0.max(b.0).max(c.0).min(self.width as i64 - 1);
+        let min_y = a.1.min(b.1).min(c.1).max(0);
+        let max_y = a.1.max(b.1).max(c.1).min(self.height as i64 - 1);
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let p = (x, y);
+                let (w0, w1, w2) = (edge(b, c, p), edge(c, a, p), edge(a, b, p));
+                let inside = if area > 0 {
+                    w0 >= 0 && w1 >= 0 && w2 >= 0
+                } else {
+                    w0 <= 0 && w1 <= 0 && w2 <= 0
+                };
+                if !inside {
+                    continue;
+                }
+
+                let z = area as f32
+                    / (w0 as f32 * inverse.0 + w1 as f32 * inverse.1 + w2 as f32 * inverse.2);
+                let i = (y as usize) * self.width + (x as usize);
+                if z < self.depth[i] {
+                    self.depth[i] = z;
+                    buffer[i] = color;
+                }
+            }
+        }
+    }
+}
+
+/// Scales the RGB channels of `base` by `intensity` (clamped to `[0, 1]`),
+/// e.g. to apply white lighting.
+pub fn shade_color(base: u32, intensity: f32) -> u32 {
+    shade_color_rgb(base, (intensity, intensity, intensity))
+}
+
+/// Scales each RGB channel of `base` by the matching factor in `(r, g, b)`
+/// (clamped to `[0, 1]`), e.g. to apply colored lighting.
+pub fn shade_color_rgb(base: u32, (r, g, b): (f32, f32, f32)) -> u32 {
+    let scale = |shift: u32, k: f32| ((((base >> shift) & 0xFF) as f32 * k.clamp(0.0, 1.0)) as u32) << shift;
+    scale(16, r) | scale(8, g) | scale(0, b)
+}
+
+/// Converts an sRGB channel in `[0, 1]` to linear light.
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear-light channel in `[0, 1]` back to sRGB.
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Mixes `src` over `dst` with opacity `alpha` (clamped to `[0, 1]`).
+///
+/// With `linear` the channels are converted to linear light, blended and
+/// converted back, which keeps partially covered pixels from looking too
+/// dark; otherwise the raw sRGB values are interpolated.
+pub fn blend_color(dst: u32, src: u32, alpha: f32, linear: bool) -> u32 {
+    let a = alpha.clamp(0.0, 1.0);
+    let channel = |shift: u32| {
+        let d = ((dst >> shift) & 0xFF) as f32 / 255.0;
+        let s = ((src >> shift) & 0xFF) as f32 / 255.0;
+        let mixed = if linear {
+            linear_to_srgb(srgb_to_linear(d) * (1.0 - a) + srgb_to_linear(s) * a)
+        } else {
+            d * (1.0 - a) + s * a
+        };
+        ((mixed * 255.0).round() as u32).min(255) << shift
+    };
+    channel(16) | channel(8) | channel(0)
+}
+
+/// Opacity settings for `draw_line_alpha`.
+#[derive(Clone, Copy)]
+pub struct Blend {
+    /// Opacity of the line, 0 (invisible) to 1 (opaque).
+    pub alpha: f32,
+    /// Blend in linear light instead of raw sRGB (see `blend_color`).
+    pub linear: bool,
+}
+
+/// Draws a translucent Bresenham line, blending `color` over the pixels
+/// already in the `buffer` (see `blend_color`).
+pub fn draw_line_alpha(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    start: (usize, usize),
+    end: (usize, usize),
+    color: u32,
+    blend: Blend,
+) {
+    bresenham(start, end, |x, y, _| {
+        if x >= 0 && x < width as i32 && y >= 0 && y < height as i32 {
+            let index = (y as usize) * width + (x as usize);
+            buffer[index] = blend_color(buffer[index], color, blend.alpha, blend.linear);
+        }
+    });
+}
+
+/// Draws an anti-aliased line using Xiaolin Wu's algorithm.
+///
+/// The line is walked along its major axis; at each step the two pixels
+/// straddling the ideal line share the coverage, and `color` is blended over
+/// the existing pixel by that coverage (see `blend_color`). Endpoints are
+/// floating-point, so the line starts and ends at subpixel positions.
+/// Pixels outside the `width` x `height` surface are skipped.
+pub fn draw_line_aa(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    (x0, y0): (f32, f32),
+    (x1, y1): (f32, f32),
+    color: u32,
+    linear: bool,
+) {
+    // Wu's algorithm puts pixel centers on whole numbers, while screen
+    // positions put pixel `i` at `i .. i + 1`
+    let (x0, y0, x1, y1) = (x0 - 0.5, y0 - 0.5, x1 - 0.5, y1 - 0.5);
+    let steep = (y1 - y0).abs() > (x1 - x0).abs();
+    let (mut x0, mut y0, mut x1, mut y1) = if steep { (y0, x0, y1, x1) } else { (x0, y0, x1, y1) };
+    if x0 > x1 {
+        std::mem::swap(&mut x0, &mut x1);
+        std::mem::swap(&mut y0, &mut y1);
+    }
+
+    let dx = x1 - x0;
+    let gradient = if dx == 0.0 { 1.0 } else { (y1 - y0) / dx };
+
+    // Blends one pixel given in (major, minor) axis order.
+    let mut plot = |major: i32, minor: i32, coverage: f32| {
+        let (x, y) = if steep { (minor, major) } else { (major, minor) };
+        if coverage > 0.0 && x >= 0 && x < width as i32 && y >= 0 && y < height as i32 {
+            let index = (y as usize) * width + (x as usize);
+            buffer[index] = blend_color(buffer[index], color, coverage, linear);
+        }
+    };
+
+    // First endpoint
+    let x_end = x0.round();
+    let y_end = y0 + gradient * (x_end - x0);
+    let x_gap = 1.0 - fpart(x0 + 0.5);
+    let x_start = x_end as i32;
+    plot(x_start, y_end.floor() as i32, (1.0 - fpart(y_end)) * x_gap);
+    plot(x_start, y_end.floor() as i32 + 1, fpart(y_end) * x_gap);
+    let mut inter_y = y_end + gradient;
+
+    // Second endpoint
+    let x_end = x1.round();
+    let y_end = y1 + gradient * (x_end - x1);
+    let x_gap = fpart(x1 + 0.5);
+    let x_stop = x_end as i32;
+    plot(x_stop, y_end.floor() as i32, (1.0 - fpart(y_end)) * x_gap);
+    plot(x_stop, y_end.floor() as i32 + 1, fpart(y_end) * x_gap);
+
+    // Main span between the endpoints
+    for x in (x_start + 1)..x_stop {
+        plot(x, inter_y.floor() as i32, 1.0 - fpart(inter_y));
+        plot(x, inter_y.floor() as i32 + 1, fpart(inter_y));
+        inter_y += gradient;
+    }
+}
+
+/// Fractional part of `v`, in `[0, 1)` also for negative values.
+fn fpart(v: f32) -> f32 {
+    v - v.floor()
+}
+
+/// Sample positions within a pixel for a `CoverageBuffer`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SamplePattern {
+    /// One sample at the pixel center: no anti-aliasing, for comparison.
+    X1,
+    /// Two samples on a diagonal.
+    X2,
+    /// Four samples on a rotated grid, so near-horizontal and near-vertical
+    /// edges each see four distinct sample rows or columns.
+    X4,
+}
+
+impl SamplePattern {
+    /// Sample offsets from the pixel's top-left corner, in pixels (the
+    /// standard Direct3D positions).
+    pub fn offsets(self) -> &'static [(f32, f32)] {
+        match self {
+            SamplePattern::X1 => &[(0.5, 0.5)],
+            SamplePattern::X2 => &[(0.75, 0.75), (0.25, 0.25)],
+            SamplePattern::X4 => &[(0.375, 0.125), (0.875, 0.375), (0.125, 0.625), (0.625, 0.875)],
+        }
+    }
+
+    /// Samples per pixel.
+    pub fn count(self) -> usize {
+        self.offsets().len()
+    }
+
+    /// The next pattern, cycling 1x, 2x, 4x.
+    pub fn next(self) -> SamplePattern {
+        match self {
+            SamplePattern::X1 => SamplePattern::X2,
+            SamplePattern::X2 => SamplePattern::X4,
+            SamplePattern::X4 => SamplePattern::X1,
+        }
+    }
+}
+
+/// MSAA-style anti-aliasing for a whole frame of lines.
+///
+/// Every pixel a line passes has its samples (at the positions of a
+/// `SamplePattern`) tested against the line, 1 pixel wide, and records which
+/// of them were hit plus the color that hit them. `resolve` then blends that
+/// color over the frame by the covered fraction. Unlike Wu lines, which
+/// spread each line over two pixels by its own distance, every edge gets the
+/// same regular coverage, at the cost of one mask and one color per pixel.
+pub struct CoverageBuffer {
+    width: usize,
+    height: usize,
+    pattern: SamplePattern,
+    /// Bit `i` is set when sample `i` of the pixel was covered.
+    masks: Vec<u8>,
+    colors: Vec<u32>,
+}
+
+impl CoverageBuffer {
+    pub fn new(width: usize, height: usize, pattern: SamplePattern) -> CoverageBuffer {
+        CoverageBuffer {
+            width,
+            height,
+            pattern,
+            masks: vec![0; width * height],
+            colors: vec![0; width * height],
+        }
+    }
+
+    /// Rasterizes a line between two subpixel screen positions, marking the
+    /// samples within half a pixel of it. Pixels outside the buffer are skipped.
+    ///
+    /// The line is walked one pixel at a time along its major axis, testing
+    /// the pixel it crosses and its two neighbors across the line.
+    pub fn draw_line(&mut self, start: (f32, f32), end: (f32, f32), color: u32) {
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let length_sq = dx * dx + dy * dy;
+        // Squared distance from a point to the segment
+        let distance_sq = |(px, py): (f32, f32)| {
+            let t = if length_sq > 0.0 { (((px - start.0) * dx + (py - start.1) * dy) / length_sq).clamp(0.0, 1.0) } else { 0.0 };
+            let (cx, cy) = (start.0 + dx * t - px, start.1 + dy * t - py);
+            cx * cx + cy * cy
+        };
+
+        // Major axis coordinate `a`, minor `b`; `pixel(a, b)` maps back to (x, y)
+        let steep = dy.abs() > dx.abs();
+        let (a0, a1, b0, slope) = if steep {
+            (start.1.min(end.1), start.1.max(end.1), if start.1 < end.1 { start.0 } else { end.0 }, if dy != 0.0 { dx / dy } else { 0.0 })
+        } else {
+            (start.0.min(end.0), start.0.max(end.0), if start.0 < end.0 { start.1 } else { end.1 }, if dx != 0.0 { dy / dx } else { 0.0 })
+        };
+        let pixel = |a: i32, b: i32| if steep { (b, a) } else { (a, b) };
+
+        for a in (a0 - 0.5).floor() as i32..=(a1 + 0.5).floor() as i32 {
+            let b = b0 + ((a as f32 + 0.5).clamp(a0, a1) - a0) * slope;
+            for b in b.floor() as i32 - 1..=b.floor() as i32 + 1 {
+                let (x, y) = pixel(a, b);
+                if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+                    continue;
+                }
+                let covered = self
+                    .pattern
+                    .offsets()
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, &(ox, oy))| distance_sq((x as f32 + ox, y as f32 + oy)) <= 0.25)
+                    .fold(0u8, |mask, (i, _)| mask | 1 << i);
+                if covered != 0 {
+                    let index = y as usize * self.width + x as usize;
+                    self.masks[index] |= covered;
+                    self.colors[index] = color;
+                }
+            }
+        }
+    }
+
+    /// Blends every covered pixel over `buffer` by its fraction of covered
+    /// samples (see `blend_color`).
+    pub fn resolve(&self, buffer: &mut [u32], linear: bool) {
+        for ((pixel, &mask), &color) in buffer.iter_mut().zip(&self.masks).zip(&self.colors) {
+            if mask != 0 {
+                let coverage = mask.count_ones() as f32 / self.pattern.count() as f32;
+                *pixel = blend_color(*pixel, color, coverage, linear);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: usize = 8;
+    const H: usize = 6;
+
+    /// Fills `rect` into a cleared buffer and returns the indices of the pixels set.
+    fn filled(rect: (isize, isize, usize, usize)) -> Vec<usize> {
+        let mut buffer = vec![0; W * H];
+        fill_rect(&mut buffer, W, H, rect, 1);
+        (0..buffer.len()).filter(|&i| buffer[i] == 1).collect()
+    }
+
+    /// Indices of the pixels in columns `x0..x1` of rows `y0..y1`.
+    fn pixels(x0: usize, x1: usize, y0: usize, y1: usize) -> Vec<usize> {
+        (y0..y1).flat_map(|y| (x0..x1).map(move |x| y * W + x)).collect()
+    }
+
+    #[test]
+    fn fill_rect_clips_at_every_edge() {
+        // Crossing the left, top, right and bottom edges
+        assert_eq!(filled((-2, 1, 4, 2)), pixels(0, 2, 1, 3));
+        assert_eq!(filled((3, -3, 2, 5)), pixels(3, 5, 0, 2));
+        assert_eq!(filled((6, 2, 5, 1)), pixels(6, 8, 2, 3));
+        assert_eq!(filled((1, 4, 2, 9)), pixels(1, 3, 4, 6));
+        // Covering the whole buffer, from past the top-left corner
+        assert_eq!(filled((-5, -5, 100, 100)), pixels(0, W, 0, H));
+    }
+
+    #[test]
+    fn fill_rect_outside_the_buffer_draws_nothing() {
+        assert!(filled((-4, 0, 4, 3)).is_empty());
+        assert!(filled((0, H as isize, 3, 3)).is_empty());
+        assert!(filled((W as isize + 1, 1, 3, 3)).is_empty());
+        assert!(filled((0, 0, 0, 3)).is_empty());
+        assert!(filled((isize::MAX, isize::MAX, usize::MAX, usize::MAX)).is_empty());
+        assert!(filled((isize::MIN, 0, 4, 3)).is_empty());
+    }
+
+    #[test]
+    fn bresenham_steps_from_start_to_end() {
+        let mut plotted = Vec::new();
+        bresenham((1, 4), (7, 1), |x, y, t| plotted.push((x, y, t)));
+        // One pixel per column, from the start pixel at 0 to the end pixel at 1
+        assert_eq!(plotted.len(), 7);
+        assert_eq!(plotted[0], (1, 4, 0.0));
+        assert_eq!(plotted[6], (7, 1, 1.0));
+        assert!(plotted.windows(2).all(|w| w[1].0 == w[0].0 + 1 && w[1].2 > w[0].2));
     }
 }
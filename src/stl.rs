@@ -0,0 +1,94 @@
+//! Loader for ASCII and binary `.stl` meshes.
+
+use crate::model::Model;
+use m3str3_cube::transformations::Point3;
+use std::collections::HashMap;
+
+/// Size of the binary header before the triangle count.
+const HEADER_SIZE: usize = 80;
+/// Size of one binary triangle: normal, three vertices (12 little-endian
+/// `f32`s) and a 2-byte attribute count.
+const TRIANGLE_SIZE: usize = 50;
+
+/// Parses a 3D model from the contents of an ASCII or binary `.stl` file.
+/// ---------------------------------------------------------------------
+/// STL stores a soup of triangles, each with its own copy of its three
+/// corners, so corners with identical coordinates are merged into one
+/// vertex and the triangles are added as faces (which builds the edges).
+/// The stored facet normals are ignored; they're recomputed from the winding.
+///
+/// Binary files often start with `solid` too, so the format is told apart
+/// by size: a binary file is exactly 84 bytes plus 50 per triangle of the
+/// count stored at byte 80.
+pub fn parse_stl(bytes: &[u8]) -> Result<Model, String> {
+    let is_binary = bytes.len() >= HEADER_SIZE + 4 && {
+        let count = u32::from_le_bytes(bytes[HEADER_SIZE..HEADER_SIZE + 4].try_into().unwrap()) as usize;
+        count.checked_mul(TRIANGLE_SIZE).and_then(|size| size.checked_add(HEADER_SIZE + 4)) == Some(bytes.len())
+    };
+
+    let triangles = if is_binary {
+        parse_binary(&bytes[HEADER_SIZE + 4..])
+    } else if bytes.trim_ascii_start().starts_with(b"solid") {
+        let contents = std::str::from_utf8(bytes).map_err(|_| "ASCII STL is not valid UTF-8".to_string())?;
+        parse_ascii(contents)?
+    } else {
+        return Err("Not an STL file (no 'solid' header, and the size doesn't match a binary one)".to_string());
+    };
+
+    let mut model = Model::default();
+    let mut index: HashMap<(u32, u32, u32), usize> = HashMap::new();
+    for triangle in triangles {
+        let face = triangle
+            .iter()
+            .map(|&(x, y, z)| {
+                *index.entry((x.to_bits(), y.to_bits(), z.to_bits())).or_insert_with(|| {
+                    model.vertices.push((x, y, z));
+                    model.vertices.len() - 1
+                })
+            })
+            .collect();
+        model.add_face(face);
+    }
+    Ok(model)
+}
+
+/// Reads the corners of the binary triangles following the header.
+fn parse_binary(body: &[u8]) -> Vec<[Point3; 3]> {
+    let float = |bytes: &[u8], i: usize| f32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap());
+    body.chunks_exact(TRIANGLE_SIZE)
+        .map(|t| {
+            // Floats 0-2 are the normal, then three corners of three floats
+            let corner = |c: usize| (float(t, 3 + c * 3), float(t, 4 + c * 3), float(t, 5 + c * 3));
+            [corner(0), corner(1), corner(2)]
+        })
+        .collect()
+}
+
+/// Reads the `vertex x y z` lines of every `facet ... endfacet` block.
+fn parse_ascii(contents: &str) -> Result<Vec<[Point3; 3]>, String> {
+    let mut triangles = Vec::new();
+    let mut corners: Vec<Point3> = Vec::new();
+
+    for (number, line) in contents.lines().enumerate() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts.first() {
+            Some(&"vertex") => {
+                let coords: Vec<f32> = parts[1..].iter().filter_map(|c| c.parse().ok()).collect();
+                match coords[..] {
+                    [x, y, z] => corners.push((x, y, z)),
+                    _ => return Err(format!("Invalid vertex on line {}: {}", number + 1, line.trim())),
+                }
+            }
+            Some(&"endfacet") => {
+                match corners[..] {
+                    [a, b, c] => triangles.push([a, b, c]),
+                    _ => return Err(format!("Facet ending on line {} has {} vertices, expected 3", number + 1, corners.len())),
+                }
+                corners.clear();
+            }
+            _ => {}
+        }
+    }
+
+    Ok(triangles)
+}
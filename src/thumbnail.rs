@@ -0,0 +1,43 @@
+//! Headless rendering of small wireframe previews, for batch thumbnails.
+
+use crate::model::{self, Model};
+use m3str3_cube::transformations::{self, Screen};
+use m3str3_cube::rendering;
+
+/// Renders the model's wireframe into a new `size` x `size` buffer.
+/// ---------------------------------------------------------------------
+/// The model is centered on its bounding box and rotated by `angles`
+/// (X then Y, in radians). The camera is placed at three times the radius of
+/// the bounding sphere, with the scale chosen so the sphere fills about 90%
+/// of the frame, whatever the model's size. Triangulation diagonals are
+/// left out, like in the viewer's default wireframe.
+pub fn render(model: &Model, size: usize, angles: (f32, f32), color: u32, background: u32) -> Vec<u32> {
+    let mut buffer = vec![background; size * size];
+
+    let (min, max) = model.bounding_box();
+    let center = ((min.0 + max.0) / 2.0, (min.1 + max.1) / 2.0, (min.2 + max.2) / 2.0);
+    let radius = (model.diagonal() / 2.0).max(f32::EPSILON);
+    let distance = 3.0 * radius;
+    let scale = 0.9 * size as f32;
+    let screen = Screen::new(size, size);
+
+    let rotation = transformations::build_rotation_matrix(angles.0, angles.1, 0.0);
+    let projected: Vec<Option<(usize, usize)>> = model
+        .vertices
+        .iter()
+        .map(|&(x, y, z)| {
+            let (x, y, z) = rotation.apply((x - center.0, y - center.1, z - center.2));
+            transformations::project_perspective(x, y, z, distance, scale, screen)
+        })
+        .collect();
+
+    for (&(a, b), &flags) in model.edges.iter().zip(&model.edge_flags) {
+        if model::is_diagonal_only(flags) {
+            continue;
+        }
+        if let (Some(start), Some(end)) = (projected[a], projected[b]) {
+            rendering::draw_line(&mut buffer, size, size, start, end, color);
+        }
+    }
+    buffer
+}
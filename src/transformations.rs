@@ -1,9 +1,7 @@
+use std::ops::{Add, Mul, Sub};
+
 pub fn rotate_x(x: f32, y: f32, z: f32, angle: f32) -> (f32, f32, f32) {
-    let cos_a = angle.cos();
-    let sin_a = angle.sin();
-    let y_new = y * cos_a - z * sin_a;
-    let z_new = y * sin_a + z * cos_a;
-    (x, y_new, z_new)
+    Mat3::rotation_x(angle).apply((x, y, z))
 }
 
 pub fn rotate_y(x: f32, y: f32, z: f32, angle: f32) -> (f32, f32, f32) {
@@ -15,40 +13,372 @@ pub fn rotate_y(x: f32, y: f32, z: f32, angle: f32) -> (f32, f32, f32) {
 }
 
 pub fn rotate_z(x: f32, y: f32, z: f32, angle: f32) -> (f32, f32, f32) {
-    let cos_a = angle.cos();
-    let sin_a = angle.sin();
-    let x_new = x * cos_a - y * sin_a;
-    let y_new = x * sin_a + y * cos_a;
-    (x_new, y_new, z)
+    Mat3::rotation_z(angle).apply((x, y, z))
+}
+
+/// Rotates a point around X, then Y, then Z (the order used by the viewer).
+/// To rotate many points by the same angles, use `build_rotation_matrix`.
+pub fn rotate(x: f32, y: f32, z: f32, angle_x: f32, angle_y: f32, angle_z: f32) -> (f32, f32, f32) {
+    let (x, y, z) = rotate_x(x, y, z, angle_x);
+    let (x, y, z) = rotate_y(x, y, z, angle_y);
+    rotate_z(x, y, z, angle_z)
+}
+
+/// The combined rotation around X, then Y, then Z (the order used by the
+/// viewer). Build it once per frame and rotate every point with plain
+/// multiplies (`Mat3::apply`) instead of recomputing the sines and cosines.
+pub fn build_rotation_matrix(angle_x: f32, angle_y: f32, angle_z: f32) -> Mat3 {
+    Mat3::rotation(angle_x, angle_y, angle_z)
+}
+
+/// A 3D vector with the usual arithmetic, convertible to and from `Point3`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vec3 {
+    pub fn new(x: f32, y: f32, z: f32) -> Vec3 {
+        Vec3 { x, y, z }
+    }
+
+    pub fn dot(self, other: Vec3) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Vec3) -> Vec3 {
+        Vec3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Scales the vector to unit length, leaving zero vectors untouched.
+    pub fn normalize(self) -> Vec3 {
+        let len = self.length();
+        if len > 0.0 { self * (1.0 / len) } else { self }
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<f32> for Vec3 {
+    type Output = Vec3;
+    fn mul(self, k: f32) -> Vec3 {
+        Vec3::new(self.x * k, self.y * k, self.z * k)
+    }
+}
+
+impl From<Point3> for Vec3 {
+    fn from((x, y, z): Point3) -> Vec3 {
+        Vec3::new(x, y, z)
+    }
+}
+
+impl From<Vec3> for Point3 {
+    fn from(v: Vec3) -> Point3 {
+        (v.x, v.y, v.z)
+    }
+}
+
+/// A 3x3 matrix (row-major), used for rotations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat3 {
+    pub rows: [[f32; 3]; 3],
+}
+
+impl Mat3 {
+    pub fn rotation_x(angle: f32) -> Mat3 {
+        let (sin_a, cos_a) = angle.sin_cos();
+        Mat3 { rows: [[1.0, 0.0, 0.0], [0.0, cos_a, -sin_a], [0.0, sin_a, cos_a]] }
+    }
+
+    pub fn rotation_y(angle: f32) -> Mat3 {
+        let (sin_a, cos_a) = angle.sin_cos();
+        Mat3 { rows: [[cos_a, 0.0, sin_a], [0.0, 1.0, 0.0], [-sin_a, 0.0, cos_a]] }
+    }
+
+    pub fn rotation_z(angle: f32) -> Mat3 {
+        let (sin_a, cos_a) = angle.sin_cos();
+        Mat3 { rows: [[cos_a, -sin_a, 0.0], [sin_a, cos_a, 0.0], [0.0, 0.0, 1.0]] }
+    }
+
+    /// Rotation around X, then Y, then Z, like `rotate`:
+    /// $$ R = R_z R_y R_x $$
+    pub fn rotation(angle_x: f32, angle_y: f32, angle_z: f32) -> Mat3 {
+        Mat3::rotation_z(angle_z) * Mat3::rotation_y(angle_y) * Mat3::rotation_x(angle_x)
+    }
+
+    /// The transpose, which for a rotation is its inverse.
+    pub fn transpose(&self) -> Mat3 {
+        Mat3 { rows: std::array::from_fn(|i| std::array::from_fn(|j| self.rows[j][i])) }
+    }
+
+    /// Multiplies the matrix by the point taken as a column vector.
+    pub fn apply(&self, (x, y, z): Point3) -> Point3 {
+        let row = |r: [f32; 3]| r[0] * x + r[1] * y + r[2] * z;
+        (row(self.rows[0]), row(self.rows[1]), row(self.rows[2]))
+    }
+}
+
+impl Mul for Mat3 {
+    type Output = Mat3;
+    fn mul(self, other: Mat3) -> Mat3 {
+        let rows = std::array::from_fn(|i| {
+            std::array::from_fn(|j| (0..3).map(|k| self.rows[i][k] * other.rows[k][j]).sum())
+        });
+        Mat3 { rows }
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+    fn mul(self, v: Vec3) -> Vec3 {
+        self.apply(v.into()).into()
+    }
+}
+
+/// Default near-plane distance: points closer to the camera than this are
+/// clipped, since tiny depths project to enormous screen coordinates.
+pub const DEFAULT_NEAR: f32 = 0.01;
+
+/// The surface points are projected onto.
+#[derive(Clone, Copy)]
+pub struct Screen {
+    pub width: usize,
+    pub height: usize,
+    /// Lens shift: offset of the projection center from the middle of the
+    /// screen, in pixels. Shifting keeps vertical lines parallel, unlike
+    /// tilting the camera.
+    pub shift: (f32, f32),
+    /// Near-plane distance from the camera; closer points aren't projected.
+    pub near: f32,
+    /// Displayed width of a pixel relative to its height. It is 1 unless the
+    /// window stretches the buffer unevenly, and x is divided by it so the
+    /// model keeps its proportions on screen.
+    pub pixel_aspect: f32,
+    /// Orthographic depth: when set, every point is scaled as if it were this
+    /// far from the camera, so there is no foreshortening.
+    pub orthographic: Option<f32>,
+}
+
+impl Screen {
+    /// A perspective screen with the projection center in the middle and the
+    /// default near plane.
+    pub fn new(width: usize, height: usize) -> Screen {
+        Screen { width, height, shift: (0.0, 0.0), near: DEFAULT_NEAR, pixel_aspect: 1.0, orthographic: None }
+    }
 }
 
 /// Projects a 3D point \((x, y, z)\) onto a 2D plane using perspective projection.
+/// Points closer to the camera than the near plane (`screen.near`) give `None`.
+/// With `screen.orthographic` set to `d`, \(z + \text{distance}\) is replaced by
+/// `d` below, giving an orthographic projection clipped the same way.
 ///
 /// Formula for projection:
-/// $$ u = x \cdot \frac{\text{scale}}{z + \text{distance}} + \frac{\text{screen\_width}}{2} $$
-/// $$ v = -y \cdot \frac{\text{scale}}{z + \text{distance}} + \frac{\text{screen\_height}}{2} $$
+/// $$ u = \frac{x}{\text{pixel\_aspect}} \cdot \frac{\text{scale}}{z + \text{distance}} + \frac{\text{screen\_width}}{2} + \text{shift}_x $$
+/// $$ v = -y \cdot \frac{\text{scale}}{z + \text{distance}} + \frac{\text{screen\_height}}{2} + \text{shift}_y $$
 pub fn project_perspective(
     x: f32,
     y: f32,
     z: f32,
     distance: f32,
     scale: f32,
-    screen_width: usize,
-    screen_height: usize,
+    screen: Screen,
 ) -> Option<(usize, usize)> {
-    let z_cam = z + distance;
+    project_depth(x, y, z, distance, scale, screen).map(|(u, v, _)| (u, v))
+}
+
+/// Like `project_perspective`, but also returns the camera-space depth
+/// `z + distance` of the point, for depth testing.
+pub fn project_depth(
+    x: f32,
+    y: f32,
+    z: f32,
+    distance: f32,
+    scale: f32,
+    screen: Screen,
+) -> Option<(usize, usize, f32)> {
+    project_subpixel(x, y, z, distance, scale, screen).map(|(u, v)| (u as usize, v as usize, z + distance))
+}
 
-    if z_cam <= 0.0 {
+/// Like `project_perspective`, but keeps the fractional part of the screen
+/// position, for drawing that works at subpixel precision.
+pub fn project_subpixel(
+    x: f32,
+    y: f32,
+    z: f32,
+    distance: f32,
+    scale: f32,
+    screen: Screen,
+) -> Option<(f32, f32)> {
+    let z_cam = z + distance;
+    if z_cam < screen.near {
         return None;
     }
 
-    let factor = scale / z_cam;
-    let u = x * factor + (screen_width as f32) / 2.0;
-    let v = -y * factor + (screen_height as f32) / 2.0;
-
-    if u >= 0.0 && u < screen_width as f32 && v >= 0.0 && v < screen_height as f32 {
-        Some((u as usize, v as usize))
+    let (u, v) = project_camera((x, y, z_cam), scale, screen);
+    if u >= 0.0 && u < screen.width as f32 && v >= 0.0 && v < screen.height as f32 {
+        Some((u, v))
     } else {
         None
     }
 }
+
+/// Projects a point given in camera space (`z` measured from the camera,
+/// in front of the near plane) without rejecting positions off the screen.
+pub fn project_camera((x, y, z_cam): Point3, scale: f32, screen: Screen) -> (f32, f32) {
+    let factor = scale / screen.orthographic.unwrap_or(z_cam);
+    let u = x * factor / screen.pixel_aspect + (screen.width as f32) / 2.0 + screen.shift.0;
+    let v = -y * factor + (screen.height as f32) / 2.0 + screen.shift.1;
+    (u, v)
+}
+
+/// Clips a camera-space segment to the near plane `z = near`, moving an
+/// endpoint behind it onto the plane. Returns `None` when the whole segment
+/// lies behind the plane.
+pub fn clip_line(start: Point3, end: Point3, near: f32) -> Option<(Point3, Point3)> {
+    let (a, b) = (start.2 - near, end.2 - near);
+    if a < 0.0 && b < 0.0 {
+        return None;
+    }
+    // Where the segment crosses the plane; t = 0 at start, 1 at end
+    let crossing = || {
+        let t = a / (a - b);
+        (start.0 + (end.0 - start.0) * t, start.1 + (end.1 - start.1) * t, near)
+    };
+    match (a < 0.0, b < 0.0) {
+        (true, _) => Some((crossing(), end)),
+        (_, true) => Some((start, crossing())),
+        _ => Some((start, end)),
+    }
+}
+
+/// Clips a projected segment to the screen rectangle (Liang–Barsky, via a
+/// flat `Aabb`). Returns `None` when it lies completely off the screen.
+pub fn clip_to_screen(start: (f32, f32), end: (f32, f32), screen: Screen) -> Option<((f32, f32), (f32, f32))> {
+    let bounds = Aabb {
+        min: (0.0, 0.0, 0.0),
+        max: (screen.width.saturating_sub(1) as f32, screen.height.saturating_sub(1) as f32, 0.0),
+    };
+    let (a, b) = bounds.clip_segment((start.0, start.1, 0.0), (end.0, end.1, 0.0))?;
+    Some(((a.0, a.1), (b.0, b.1)))
+}
+
+/// A point in 3D space.
+pub type Point3 = (f32, f32, f32);
+
+/// Axis-aligned box given by its minimum and maximum corners.
+#[derive(Clone, Copy)]
+pub struct Aabb {
+    pub min: Point3,
+    pub max: Point3,
+}
+
+impl Aabb {
+    /// Whether `p` lies inside the box (boundary included).
+    pub fn contains(&self, (x, y, z): Point3) -> bool {
+        x >= self.min.0 && x <= self.max.0
+            && y >= self.min.1 && y <= self.max.1
+            && z >= self.min.2 && z <= self.max.2
+    }
+
+    /// The 8 corners; bit 0/1/2 of the index selects max over min on X/Y/Z.
+    pub fn corners(&self) -> [Point3; 8] {
+        let pick = |i: usize, bit: usize, lo: f32, hi: f32| if i & bit != 0 { hi } else { lo };
+        std::array::from_fn(|i| {
+            (
+                pick(i, 1, self.min.0, self.max.0),
+                pick(i, 2, self.min.1, self.max.1),
+                pick(i, 4, self.min.2, self.max.2),
+            )
+        })
+    }
+
+    /// Clips the segment `p0 -> p1` to the box (Liang–Barsky).
+    ///
+    /// With \( p(t) = p_0 + t (p_1 - p_0) \), each slab \( lo \le p \le hi \) of
+    /// each axis narrows the visible interval \( [t_0, t_1] \subseteq [0, 1] \).
+    /// Returns `None` when the segment lies completely outside.
+    pub fn clip_segment(&self, p0: Point3, p1: Point3) -> Option<(Point3, Point3)> {
+        let start = [p0.0, p0.1, p0.2];
+        let delta = [p1.0 - p0.0, p1.1 - p0.1, p1.2 - p0.2];
+        let lo = [self.min.0, self.min.1, self.min.2];
+        let hi = [self.max.0, self.max.1, self.max.2];
+        let (mut t0, mut t1) = (0.0_f32, 1.0_f32);
+
+        for axis in 0..3 {
+            if delta[axis] == 0.0 {
+                if start[axis] < lo[axis] || start[axis] > hi[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let ta = (lo[axis] - start[axis]) / delta[axis];
+            let tb = (hi[axis] - start[axis]) / delta[axis];
+            t0 = t0.max(ta.min(tb));
+            t1 = t1.min(ta.max(tb));
+            if t0 > t1 {
+                return None;
+            }
+        }
+
+        let at = |t: f32| (p0.0 + delta[0] * t, p0.1 + delta[1] * t, p0.2 + delta[2] * t);
+        Some((at(t0), at(t1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pixel width and height of a unit square centered on the view axis.
+    fn projected_size(screen: Screen) -> (f32, f32) {
+        let project = |x, y| project_subpixel(x, y, 0.0, 2.0, 300.0, screen).unwrap();
+        let (left, top) = project(-0.5, 0.5);
+        let (right, bottom) = project(0.5, -0.5);
+        (right - left, bottom - top)
+    }
+
+    #[test]
+    fn unit_square_keeps_its_proportions_at_2_to_1() {
+        let (w, h) = projected_size(Screen::new(800, 400));
+        assert!((w - h).abs() < 1e-3, "{} x {}", w, h);
+        // Same size as in a square window of the same height
+        assert_eq!(projected_size(Screen::new(400, 400)), (w, h));
+    }
+
+    #[test]
+    fn stretched_pixels_are_corrected() {
+        // A 400 x 400 buffer shown in an 800 x 400 window: pixels are twice as wide
+        let (w, h) = projected_size(Screen { pixel_aspect: 2.0, ..Screen::new(400, 400) });
+        assert!((w * 2.0 - h).abs() < 1e-3, "{} x {}", w, h);
+    }
+
+    #[test]
+    fn rotate_matches_the_rotation_matrix() {
+        let (angles, point) = ((0.3, -1.2, 2.5), (1.0, -2.0, 0.5));
+        let (x, y, z) = rotate(point.0, point.1, point.2, angles.0, angles.1, angles.2);
+        let (mx, my, mz) = build_rotation_matrix(angles.0, angles.1, angles.2).apply(point);
+        assert!((x - mx).abs() < 1e-5 && (y - my).abs() < 1e-5 && (z - mz).abs() < 1e-5);
+    }
+}